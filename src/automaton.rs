@@ -0,0 +1,476 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::any::Any;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use crate::utils::GenericError;
+use crate::grid::{Grid, MOORE_OFFSETS, VON_NEUMANN_OFFSETS};
+
+/// Rows of cells computed at once by a thread when stepping in parallel
+const BAND_ROWS: usize = 16;
+
+/// Cells a rule looks at around each cell
+#[derive(Debug, Clone)]
+pub enum Neighbourhood<T> {
+    /// The eight surrounding cells
+    Moore,
+    /// The four orthogonally surrounding cells
+    VonNeumann,
+    /// The first cell seen in each of the eight directions, skipping the transparent ones
+    LineOfSight(fn(&T) -> bool),
+    /// The cells at the given (dx, dy) offsets
+    Offsets(Vec<(isize, isize)>),
+}
+
+impl<T> Neighbourhood<T> {
+    fn get_offsets(&self) -> &[(isize, isize)] {
+        match self {
+            Neighbourhood::Moore | Neighbourhood::LineOfSight(_) => &MOORE_OFFSETS,
+            Neighbourhood::VonNeumann => &VON_NEUMANN_OFFSETS,
+            Neighbourhood::Offsets(offsets) => offsets,
+        }
+    }
+}
+
+impl FromStr for Neighbourhood<bool> {
+    type Err = GenericError;
+
+    /// `moore`, `von-neumann`, `line-of-sight` (seeing through inactive cells) or offsets as `<dx>,<dy>;...`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "moore" => Ok(Neighbourhood::Moore),
+            "von-neumann" => Ok(Neighbourhood::VonNeumann),
+            "line-of-sight" => Ok(Neighbourhood::LineOfSight(|cell| !*cell)),
+            _ => s.split(';')
+                .map(|offset| match offset.split(',').map(|delta| delta.trim().parse::<isize>()).collect::<Vec<_>>().as_slice() {
+                    [Ok(dx), Ok(dy)] if (*dx, *dy) != (0, 0) => Ok((*dx, *dy)),
+                    _ => Err(GenericError::Usage(format!("Invalid neighbourhood offset '{}'", offset))),
+                })
+                .collect::<Result<Vec<(isize, isize)>, GenericError>>()
+                .map(Neighbourhood::Offsets),
+        }
+    }
+}
+
+/// Transition of a cell, given its current state and its neighbours
+pub trait Rule<T> {
+    fn apply(&self, cell: &T, neighbours: &[&T]) -> T;
+}
+
+impl<T, F> Rule<T> for F
+    where F: Fn(&T, &[&T]) -> T
+{
+    fn apply(&self, cell: &T, neighbours: &[&T]) -> T {
+        self(cell, neighbours)
+    }
+}
+
+/// Generations from which a simulation repeats itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SteadyState {
+    /// First generation of the repeated sequence
+    pub start: usize,
+    /// Length of the repeated sequence, 1 for a fixed point
+    pub period: usize,
+}
+
+impl SteadyState {
+    pub fn is_fixed_point(&self) -> bool {
+        self.period == 1
+    }
+}
+
+/// Stepping API shared by the dense and sparse automata
+pub trait Simulation {
+    /// Snapshot of a generation, equal for equal generations
+    type State: Eq + Hash;
+
+    /// Compute the next generation, returns whether anything changed
+    fn step(&mut self) -> bool;
+
+    /// Snapshot of the current generation
+    fn get_state(&self) -> Self::State;
+
+    /// Same as `run_until_steady`, `on_generation` being called on every generation from the current one
+    fn run_until_steady_with<F: FnMut(&Self)>(&mut self, max_generations: usize, mut on_generation: F) -> Result<SteadyState, GenericError>
+        where Self: Sized
+    {
+        // Brent's cycle detection: a single generation is kept, replaced by the one of each power of two
+        let mut checkpoint = (0, self.get_state());
+        let mut hashes = vec![get_hash(&checkpoint.1)];
+        on_generation(self);
+
+        for generation in 1..=max_generations {
+            self.step();
+            on_generation(self);
+            let state = self.get_state();
+            hashes.push(get_hash(&state));
+
+            if hashes[generation] == hashes[checkpoint.0] && state == checkpoint.1 {
+                let period = generation - checkpoint.0;
+                let start = (0..checkpoint.0).find(|start| hashes[*start] == hashes[start + period]).unwrap_or(checkpoint.0);
+                return Ok(SteadyState { start, period });
+            }
+            if generation.is_power_of_two() {
+                checkpoint = (generation, state);
+            }
+        }
+
+        Err(GenericError::NoSolution(format!("No steady state within {} generations", max_generations)))
+    }
+
+    /// Step until a generation repeats an earlier one, generations being numbered from the current one.
+    ///
+    /// Only one earlier generation is kept, and the period is found by comparing it in whole with the later ones.
+    /// Earlier generations are only kept as hashes, to find where the repeated sequence starts. The repeat is
+    /// found within about twice the start and period, so the simulation may step beyond the first repeat.
+    /// Fails when no repeat was found within `max_generations` steps.
+    fn run_until_steady(&mut self, max_generations: usize) -> Result<SteadyState, GenericError>
+        where Self: Sized
+    {
+        self.run_until_steady_with(max_generations, |_| {})
+    }
+}
+
+fn get_hash<T: Hash>(state: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A 2D cellular automaton over a grid, cells beyond the edges of the grid have no state
+///
+/// Generations are computed into a second buffer which is then swapped with the cells of the grid,
+/// so stepping does not allocate.
+#[derive(Debug, Clone)]
+pub struct Automaton<T, R> {
+    grid: Grid<T>,
+    buffer: Vec<T>,
+    neighbourhood: Neighbourhood<T>,
+    rule: R,
+}
+
+impl<T: Clone + PartialEq, R: Rule<T>> Automaton<T, R> {
+    pub fn new(grid: Grid<T>, neighbourhood: Neighbourhood<T>, rule: R) -> Self {
+        Automaton {
+            buffer: grid.get_cells().to_vec(),
+            grid,
+            neighbourhood,
+            rule,
+        }
+    }
+
+    /// The current generation
+    pub fn get_grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    /// Replace the content of `neighbours` with the neighbours of (x, y)
+    pub fn collect_neighbours<'a>(&'a self, x: usize, y: usize, neighbours: &mut Vec<&'a T>) {
+        neighbours.clear();
+        match &self.neighbourhood {
+            Neighbourhood::LineOfSight(is_transparent) => neighbours.extend(MOORE_OFFSETS.iter()
+                .filter_map(|direction| self.grid.ray(x, y, *direction)
+                    .map(|(_, cell)| cell)
+                    .find(|cell| !is_transparent(cell)))),
+            neighbourhood => neighbours.extend(self.grid.neighbours(x, y, neighbourhood.get_offsets())),
+        }
+    }
+
+    /// Compute the next state of the cells from `start`, returns whether any of them changed
+    fn step_band(&self, start: usize, band: &mut [T]) -> bool {
+        let mut changed = false;
+        let mut neighbours = Vec::with_capacity(self.neighbourhood.get_offsets().len());
+        let width = self.grid.get_width();
+
+        for (offset, next_cell) in band.iter_mut().enumerate() {
+            let index = start + offset;
+            let cell = &self.grid.get_cells()[index];
+            self.collect_neighbours(index % width, index / width, &mut neighbours);
+            *next_cell = self.rule.apply(cell, &neighbours);
+            changed |= next_cell != cell;
+        }
+
+        changed
+    }
+
+    fn swap_generation(&mut self, mut next: Vec<T>) {
+        self.grid.swap_cells(&mut next);
+        self.buffer = next;
+    }
+}
+
+impl<T: Clone + Eq + Hash, R: Rule<T>> Simulation for Automaton<T, R> {
+    type State = Vec<T>;
+
+    fn step(&mut self) -> bool {
+        let mut next = mem::take(&mut self.buffer);
+        let changed = self.step_band(0, &mut next);
+        self.swap_generation(next);
+
+        changed
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.grid.get_cells().to_vec()
+    }
+}
+
+/// Threads available for parallel stepping, one when unknown
+pub fn get_available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// A band of rows to compute, from the generation shared by the workers
+struct Job<T, R> {
+    automaton: Arc<Automaton<T, R>>,
+    index: usize,
+    cells: Vec<T>,
+}
+
+/// A computed band of rows, sent back by a worker
+struct Band<T> {
+    index: usize,
+    cells: Vec<T>,
+    changed: bool,
+}
+
+/// A band, or what a worker panicked with while computing it
+type BandResult<T> = Result<Band<T>, Box<dyn Any + Send>>;
+
+/// An automaton whose generations are computed by bands of rows on worker threads.
+///
+/// The workers are started once and kept alive across steps, until the automaton is dropped.
+/// The band buffers go back and forth between the automaton and the workers, so stepping does not allocate.
+/// A rule panicking on a worker makes `step` panic with the same payload.
+pub struct ParallelAutomaton<T, R> {
+    automaton: Arc<Automaton<T, R>>,
+    band_size: usize,
+    bands: Vec<Vec<T>>,
+    jobs: Option<Sender<Job<T, R>>>,
+    results: Receiver<BandResult<T>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T, R> ParallelAutomaton<T, R>
+    where T: Clone + PartialEq + Send + Sync + 'static,
+          R: Rule<T> + Send + Sync + 'static
+{
+    pub fn new(automaton: Automaton<T, R>, threads: usize) -> Self {
+        let band_size = BAND_ROWS * automaton.grid.get_width().max(1);
+        let bands = automaton.grid.get_cells().chunks(band_size).map(|band| band.to_vec()).collect();
+        let (jobs, job_receiver) = mpsc::channel::<Job<T, R>>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (result_sender, results) = mpsc::channel();
+
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let result_sender = result_sender.clone();
+                thread::spawn(move || loop {
+                    let job = job_receiver.lock().expect("A worker panicked").recv();
+                    let Job { automaton, index, mut cells } = match job {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    // A panicking rule is reported to the stepping thread, which would otherwise wait for the band
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| automaton.step_band(index * band_size, &mut cells)))
+                        .map(|changed| Band { index, cells, changed });
+                    // Release the generation before handing the band back, so that it can be replaced
+                    drop(automaton);
+                    if result_sender.send(result).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        ParallelAutomaton {
+            automaton: Arc::new(automaton),
+            band_size,
+            bands,
+            jobs: Some(jobs),
+            results,
+            workers,
+        }
+    }
+
+    /// The current generation
+    pub fn get_grid(&self) -> &Grid<T> {
+        self.automaton.get_grid()
+    }
+}
+
+impl<T, R> Simulation for ParallelAutomaton<T, R>
+    where T: Clone + Eq + Hash + Send + Sync + 'static,
+          R: Rule<T> + Send + Sync + 'static
+{
+    type State = Vec<T>;
+
+    fn step(&mut self) -> bool {
+        let mut next = mem::take(&mut Arc::get_mut(&mut self.automaton).expect("No band is being computed").buffer);
+        let jobs = self.jobs.as_ref().expect("Workers run until the automaton is dropped");
+        let band_count = self.bands.len();
+        for (index, cells) in self.bands.drain(..).enumerate() {
+            jobs.send(Job { automaton: Arc::clone(&self.automaton), index, cells }).expect("A worker panicked");
+        }
+
+        let mut bands: Vec<Option<Vec<T>>> = vec![None; band_count];
+        let mut changed = false;
+        for _ in 0..band_count {
+            let band = match self.results.recv().expect("Workers run until the automaton is dropped") {
+                Ok(band) => band,
+                Err(payload) => panic::resume_unwind(payload),
+            };
+            next[band.index * self.band_size..][..band.cells.len()].clone_from_slice(&band.cells);
+            changed |= band.changed;
+            bands[band.index] = Some(band.cells);
+        }
+        self.bands = bands.into_iter().flatten().collect();
+
+        Arc::get_mut(&mut self.automaton).expect("Workers release the generation once their band is computed")
+            .swap_generation(next);
+
+        changed
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.automaton.get_state()
+    }
+}
+
+impl<T, R> Drop for ParallelAutomaton<T, R> {
+    fn drop(&mut self) {
+        // Closing the job channel stops the workers
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::automaton::{Automaton, ParallelAutomaton, Neighbourhood, SteadyState, Simulation};
+    use crate::grid::{Grid, Edges};
+    use std::panic::{self, AssertUnwindSafe};
+
+    fn life(cell: &bool, neighbours: &[&bool]) -> bool {
+        let alive = neighbours.iter().filter(|neighbour| ***neighbour).count();
+        alive == 3 || (*cell && alive == 2)
+    }
+
+    fn parse(input: &str) -> Grid<bool> {
+        let width = input.lines().next().unwrap().len();
+        Grid::new(width, input.lines().flat_map(|line| line.chars().map(|c| c == '#')).collect()).unwrap()
+    }
+
+    #[test]
+    fn it_steps_a_blinker() {
+        let grid = parse(".....\n..#..\n..#..\n..#..\n.....");
+        let mut automaton = Automaton::new(grid.clone(), Neighbourhood::Moore, life);
+
+        assert!(automaton.step());
+        assert_eq!(automaton.get_grid(), &parse(".....\n.....\n.###.\n.....\n....."));
+        assert!(automaton.step());
+        assert_eq!(automaton.get_grid(), &grid);
+    }
+
+    #[test]
+    fn it_collects_neighbourhoods() {
+        let grid = parse("#.#\n.#.\n#.#");
+        let count = |neighbourhood: Neighbourhood<bool>, x, y| {
+            let automaton = Automaton::new(grid.clone(), neighbourhood, life);
+            let mut neighbours = vec![];
+            automaton.collect_neighbours(x, y, &mut neighbours);
+            neighbours.into_iter().filter(|cell| **cell).count()
+        };
+
+        assert_eq!(count(Neighbourhood::Moore, 1, 1), 4);
+        assert_eq!(count(Neighbourhood::VonNeumann, 1, 1), 0);
+        assert_eq!(count(Neighbourhood::Moore, 0, 0), 1);
+        assert_eq!(count(Neighbourhood::LineOfSight(|cell| !*cell), 0, 1), 3);
+        assert_eq!(count(Neighbourhood::LineOfSight(|cell| !*cell), 1, 1), 4);
+        assert_eq!(count(Neighbourhood::Offsets(vec![(2, 0), (0, 2)]), 0, 0), 2);
+    }
+
+    #[test]
+    fn it_parses_neighbourhoods() {
+        assert!(matches!("von-neumann".parse::<Neighbourhood<bool>>(), Ok(Neighbourhood::VonNeumann)));
+        assert!(matches!("line-of-sight".parse::<Neighbourhood<bool>>(), Ok(Neighbourhood::LineOfSight(_))));
+        match "2,0; -1,1".parse::<Neighbourhood<bool>>() {
+            Ok(Neighbourhood::Offsets(offsets)) => assert_eq!(offsets, vec![(2, 0), (-1, 1)]),
+            other => panic!("Expected offsets, got {:?}", other),
+        }
+
+        for invalid in &["", "hex", "1", "1,x", "0,0", "1,2,3"] {
+            assert!(invalid.parse::<Neighbourhood<bool>>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn it_detects_steady_states() {
+        let mut blinker = Automaton::new(parse(".....\n..#..\n..#..\n..#..\n....."), Neighbourhood::Moore, life);
+        assert!(blinker.clone().run_until_steady(1).is_err());
+        assert_eq!(blinker.run_until_steady(10).unwrap(), SteadyState { start: 0, period: 2 });
+
+        let mut block = Automaton::new(parse("....\n.##.\n.##.\n...."), Neighbourhood::Moore, life);
+        assert!(block.run_until_steady(10).unwrap().is_fixed_point());
+
+        let saturate = |cell: &u8, _: &[&u8]| (*cell + 1).min(4);
+        let mut counter = Automaton::new(Grid::new(1, vec![0u8]).unwrap(), Neighbourhood::Moore, saturate);
+        assert_eq!(counter.run_until_steady(10).unwrap(), SteadyState { start: 4, period: 1 });
+
+        let rotate = |cell: &u8, _: &[&u8]| (*cell + 1) % 3;
+        let mut counter = Automaton::new(Grid::new(1, vec![0u8]).unwrap(), Neighbourhood::Moore, rotate);
+        let mut generations = vec![];
+        let steady_state = counter.run_until_steady_with(10, |automaton| generations.push(automaton.get_grid().get_cells()[0]));
+        assert_eq!(steady_state.unwrap(), SteadyState { start: 0, period: 3 });
+        assert_eq!(generations, vec![0, 1, 2, 0, 1, 2, 0, 1]);
+
+        let delayed = |cell: &u8, _: &[&u8]| if *cell < 40 { *cell + 1 } else { 40 + (*cell - 40 + 1) % 7 };
+        let mut counter = Automaton::new(Grid::new(1, vec![0u8]).unwrap(), Neighbourhood::Moore, delayed);
+        assert_eq!(counter.run_until_steady(200).unwrap(), SteadyState { start: 40, period: 7 });
+    }
+
+    #[test]
+    fn it_steps_in_parallel() {
+        let rows: Vec<String> = (0..50)
+            .map(|y| (0..37).map(|x| if (x * 7 + y * 13) % 5 < 2 { '#' } else { '.' }).collect())
+            .collect();
+        let serial = Automaton::new(parse(&rows.join("\n")), Neighbourhood::Moore, life);
+
+        for threads in &[1, 3, 8] {
+            let mut serial = serial.clone();
+            let mut parallel = ParallelAutomaton::new(serial.clone(), *threads);
+            for _ in 0..5 {
+                assert_eq!(parallel.step(), serial.step());
+                assert_eq!(parallel.get_grid(), serial.get_grid());
+            }
+        }
+
+        let mut empty = ParallelAutomaton::new(Automaton::new(Grid::new(0, vec![]).unwrap(), Neighbourhood::Moore, life), 2);
+        assert!(!empty.step());
+    }
+
+    #[test]
+    fn it_reports_worker_panics() {
+        let grid = Grid::new(10, vec![0u8; 400]).unwrap();
+        let failing = |cell: &u8, neighbours: &[&u8]| if neighbours.len() < 8 { panic!("Edge cell") } else { *cell };
+        let mut parallel = ParallelAutomaton::new(Automaton::new(grid, Neighbourhood::Moore, failing), 2);
+
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| parallel.step())).is_err());
+    }
+
+    #[test]
+    fn it_wraps_around_toroidal_grids() {
+        let glider = parse(".#...\n..#..\n###..\n.....\n.....").with_edges(Edges::Toroidal);
+        let mut automaton = Automaton::new(glider, Neighbourhood::Moore, life);
+
+        assert_eq!(automaton.run_until_steady(100).unwrap(), SteadyState { start: 0, period: 20 });
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::daily_challenge::Part;
+use crate::utils::GenericError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part(Part),
+}
+
+impl Phase {
+    /// Rank of the phase when a day is run
+    fn get_order(&self) -> usize {
+        match self {
+            Phase::Parse => 0,
+            Phase::Part(part) => part.get_num(),
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+impl FromStr for Phase {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse" => Ok(Phase::Parse),
+            "part1" => Ok(Phase::Part(Part::One)),
+            "part2" => Ok(Phase::Part(Part::Two)),
+            _ => Err(GenericError::parse(format!("Unknown phase '{}'", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_durations(mut durations: Vec<Duration>) -> Self {
+        if durations.is_empty() {
+            return Stats { min: Duration::default(), median: Duration::default(), max: Duration::default() };
+        }
+
+        durations.sort();
+        let middle = durations.len() / 2;
+        let median = if durations.len().is_multiple_of(2) {
+            (durations[middle - 1] + durations[middle]) / 2
+        } else {
+            durations[middle]
+        };
+
+        Stats {
+            min: durations[0],
+            median,
+            max: durations[durations.len() - 1],
+        }
+    }
+}
+
+/// Timing statistics of one phase of a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measure {
+    pub day: usize,
+    pub phase: Phase,
+    pub stats: Stats,
+}
+
+impl Display for Measure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {:>2} {:<5} : min {:?}, median {:?}, max {:?}",
+               self.day, self.phase, self.stats.min, self.stats.median, self.stats.max)
+    }
+}
+
+impl FromStr for Measure {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('\t').collect();
+        if parts.len() != 5 {
+            return Err(GenericError::parse(format!("Invalid measure '{}'", s)));
+        }
+
+        Ok(Measure {
+            day: parts[0].parse()?,
+            phase: parts[1].parse()?,
+            stats: Stats {
+                min: Duration::from_nanos(parts[2].parse()?),
+                median: Duration::from_nanos(parts[3].parse()?),
+                max: Duration::from_nanos(parts[4].parse()?),
+            },
+        })
+    }
+}
+
+impl Measure {
+    fn to_record(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}", self.day, self.phase,
+                self.stats.min.as_nanos(), self.stats.median.as_nanos(), self.stats.max.as_nanos())
+    }
+}
+
+/// A phase whose median got slower than its baseline
+#[derive(Debug)]
+pub struct Regression<'a> {
+    pub measure: &'a Measure,
+    pub baseline: &'a Measure,
+}
+
+impl Display for Regression<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let ratio = self.measure.stats.median.as_secs_f64() / self.baseline.stats.median.as_secs_f64();
+        write!(f, "day {:>2} {:<5} : median {:?} vs {:?} in baseline ({:+.0}%)",
+               self.measure.day, self.measure.phase, self.measure.stats.median,
+               self.baseline.stats.median, (ratio - 1.0) * 100.0)
+    }
+}
+
+/// Save measures in a baseline, replacing the saved ones of the same day and phase and keeping the others
+pub fn save_baseline(file_path: &str, measures: &[Measure]) -> Result<(), GenericError> {
+    let mut baseline: Vec<Measure> = load_baseline(file_path)?.into_iter()
+        .filter(|base| !measures.iter().any(|measure| measure.day == base.day && measure.phase == base.phase))
+        .chain(measures.iter().cloned())
+        .collect();
+    baseline.sort_by_key(|measure| (measure.day, measure.phase.get_order()));
+
+    let contents: String = baseline.iter()
+        .map(|measure| measure.to_record() + "\n")
+        .collect();
+    fs::write(file_path, contents)
+        .map_err(|err| GenericError::Io { path: Some(file_path.to_string()), source: err })?;
+
+    Ok(())
+}
+
+/// Load a saved baseline, an absent file is an empty baseline
+pub fn load_baseline(file_path: &str) -> Result<Vec<Measure>, GenericError> {
+    let contents = match fs::read_to_string(file_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(GenericError::Io { path: Some(file_path.to_string()), source: err }),
+    };
+
+    contents.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse())
+        .collect()
+}
+
+/// Find the measures whose median exceeds the baseline one by more than `threshold` percent
+pub fn find_regressions<'a>(measures: &'a [Measure], baseline: &'a [Measure], threshold: f64) -> Vec<Regression<'a>> {
+    measures.iter()
+        .filter_map(|measure| {
+            baseline.iter()
+                .find(|base| base.day == measure.day && base.phase == measure.phase)
+                .map(|base| Regression { measure, baseline: base })
+        })
+        .filter(|regression| {
+            let limit = regression.baseline.stats.median.as_secs_f64() * (1.0 + threshold / 100.0);
+            regression.measure.stats.median.as_secs_f64() > limit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::{Stats, Measure, Phase, find_regressions, load_baseline, save_baseline};
+    use crate::daily_challenge::Part;
+    use crate::utils::get_temp_path;
+    use std::time::Duration;
+
+    fn measure(phase: Phase, median_ms: u64) -> Measure {
+        let median = Duration::from_millis(median_ms);
+        Measure { day: 1, phase, stats: Stats { min: median, median, max: median } }
+    }
+
+    #[test]
+    fn it_computes_stats() {
+        let stats = Stats::from_durations(vec![5, 1, 3].into_iter().map(Duration::from_millis).collect());
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.max, Duration::from_millis(5));
+
+        let stats = Stats::from_durations(vec![4, 1, 2, 3].into_iter().map(Duration::from_millis).collect());
+        assert_eq!(stats.median, Duration::from_micros(2500));
+    }
+
+    #[test]
+    fn it_round_trips_measures() {
+        let original = measure(Phase::Part(Part::Two), 12);
+        let parsed: Measure = original.to_record().parse().unwrap();
+        assert_eq!(parsed, original);
+    }
+
+    #[test]
+    fn it_keeps_other_days_when_saving() {
+        let path = get_temp_path("bench_baseline.tsv");
+        let file_path = path.to_str().unwrap();
+        let day_8 = |phase, median_ms| Measure { day: 8, ..measure(phase, median_ms) };
+
+        save_baseline(file_path, &[day_8(Phase::Parse, 1), day_8(Phase::Part(Part::One), 2), measure(Phase::Parse, 3)]).unwrap();
+        save_baseline(file_path, &[day_8(Phase::Part(Part::One), 4), day_8(Phase::Part(Part::Two), 5)]).unwrap();
+        let baseline = load_baseline(file_path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(baseline, vec![
+            measure(Phase::Parse, 3),
+            day_8(Phase::Parse, 1),
+            day_8(Phase::Part(Part::One), 4),
+            day_8(Phase::Part(Part::Two), 5),
+        ]);
+    }
+
+    #[test]
+    fn it_flags_regressions() {
+        let baseline = vec![measure(Phase::Parse, 10), measure(Phase::Part(Part::One), 10)];
+        let current = vec![
+            measure(Phase::Parse, 11),
+            measure(Phase::Part(Part::One), 15),
+            measure(Phase::Part(Part::Two), 100),
+        ];
+
+        let regressions = find_regressions(&current, &baseline, 20.0);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].measure.phase, Phase::Part(Part::One));
+    }
+}
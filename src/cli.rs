@@ -0,0 +1,815 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::fmt::Display;
+use crate::utils::GenericError;
+use crate::daily_challenge::{Part, Input};
+use crate::utils::LoadMode;
+use crate::output::Format;
+use crate::frames::FrameFormat;
+use crate::grid::Edges;
+use crate::automaton::Neighbourhood;
+
+pub const USAGE: &str = "Usage: aoc2020 [run] [--day <days>] [--part <1|2|both>] [--input [<day>=]<path>] [--example [<n>]]
+                   [--lenient] [--format <text|json|csv>] [--schema <path>] [-]
+       aoc2020 bench [--day <days>] [--part <1|2|both>] [--input [<day>=]<path>] [--iterations <n>]
+                     [--baseline <path>] [--threshold <percent>] [--save]
+       aoc2020 verify [--day <days>] [--part <1|2|both>] [--input [<day>=]<path>] [--example [<n>]]
+                      [--answers <path>]
+       aoc2020 frames [--part <1|2>] [--input <path>] [--example [<n>]] [--format <text|png|gif>]
+                      [--output <path>]
+       aoc2020 bench-grid [--size <n>]... [--threads <n>] [--iterations <n>]
+       aoc2020 expenses [--input <path>] [--example [<n>]] [--target <n>] [-k <n>]
+       aoc2020 policies [--input <path>] [--example [<n>]] [--rules <path>]
+       aoc2020 audit [--input <path>] [--example [<n>]] [--lenient] [--format <text|json>]
+       aoc2020 passports [--input <path>] [--example [<n>]] [--lenient] [--schema <path>]
+       aoc2020 cubes [--input <path>] [--dimensions <2|3|4>] [--cycles <n>]
+       aoc2020 life [--input <path>] [--neighbourhood <name|offsets>] [--edges <bounded|wrap|toroidal>]
+                    [--generations <n>] [--transpose] [--rotate <n>]
+
+  --day        days to run, as a number, a range or a list (ex: 7, 1-5, 1,3,8-11), all days by default
+  --part       part to solve, both by default
+  --input      input file, either for the single selected day or as <day>=<path>, may be repeated,
+               - reads the input from stdin, as does a lone - argument
+  --example    solve the n-th example of the puzzle statement (the first one by default) instead of the input
+  --lenient    skip and report invalid input records instead of failing
+  --format     output format of the answers or of the day 2 audit, text by default, or of the day 11 generations
+  --iterations number of runs of each phase, 10 by default
+  --baseline   file of saved timings to compare with, bench_baseline.tsv by default
+  --threshold  slowdown of the median, in percent, reported as a regression, 20 by default
+  --save       save the new timings in the baseline, replacing those of the same days and phases
+  --answers    file of known-good answers, src/answers.toml by default, examples are checked against
+               the answers of the puzzle statement
+  --output     where generations are written, stdout for text, day11-<n>.png files or day11.gif by default
+  --size       side of the random day 11 grids stepped serially then in parallel, 1000 and 2000 by default
+  --threads    threads stepping in parallel, as many as the available cores by default
+  --target     sum the day 1 expenses are matched against, 2020 by default
+  -k           number of day 1 expenses in each matching set, 2 by default
+  --rules      file of password policies checked against the day 2 entries, src/day2/policies.txt by default
+  --schema     TOML (or .json) schema the day 4 passports are checked against when running or diagnosing them,
+               the puzzle rules of src/day4/schema.toml by default
+  --dimensions dimensions the 2D slice of # and . cubes is lifted to, 3 by default
+  --cycles     cycles of Conway's rules applied to the cubes, 6 by default
+  --neighbourhood
+               cells counted around each cell of a 2D slice, moore (by default), von-neumann, line-of-sight
+               (seeing through inactive cells) or offsets as <dx>,<dy>;...
+  --edges      what lies beyond the borders of the 2D slice, bounded (by default), wrap or toroidal
+  --generations
+               generations of Conway's rules applied to the 2D slice, 4 by default
+  --transpose  swap the rows and columns of the 2D slice before the first generation
+  --rotate     quarter turns clockwise applied to the 2D slice (after --transpose) before the first generation";
+
+pub const DEFAULT_BASELINE_PATH: &str = "bench_baseline.tsv";
+pub const DEFAULT_ANSWERS_PATH: &str = "src/answers.toml";
+/// The only day whose generations can be rendered
+pub const FRAMES_DAY: usize = 11;
+pub const DEFAULT_GRID_SIZES: [usize; 2] = [1000, 2000];
+/// The day whose expense report is analysed
+pub const EXPENSES_DAY: usize = 1;
+/// The day whose password entries are checked against policies or audited
+pub const PASSWORDS_DAY: usize = 2;
+pub const DEFAULT_POLICIES_PATH: &str = "src/day2/policies.txt";
+/// The day whose passports are diagnosed
+pub const PASSPORTS_DAY: usize = 4;
+/// Slice of cubes from the puzzle statement, used without input
+pub const DEFAULT_SLICE: &str = ".#.\n..#\n###";
+/// Dimensions cubes can be lifted to
+pub const CUBE_DIMENSIONS: [usize; 3] = [2, 3, 4];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SubCommand {
+    Run,
+    Bench,
+    Verify,
+    Frames,
+    BenchGrid,
+    Expenses,
+    Policies,
+    Audit,
+    Passports,
+    Cubes,
+    Life,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(RunOptions),
+    Bench(BenchOptions),
+    Verify(VerifyOptions),
+    Frames(FramesOptions),
+    BenchGrid(GridBenchOptions),
+    Expenses(ExpensesOptions),
+    Policies(PoliciesOptions),
+    Audit(RunOptions),
+    Passports(RunOptions),
+    Cubes(CubesOptions),
+    Life(LifeOptions),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunOptions {
+    pub days: Vec<usize>,
+    pub parts: Vec<Part>,
+    pub inputs: HashMap<usize, String>,
+    pub load_mode: LoadMode,
+    pub format: Format,
+    /// 0-based index of the example to solve instead of the input files
+    pub example: Option<usize>,
+    /// Schema of the day 4 passports, the puzzle rules when none
+    pub schema_path: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchOptions {
+    pub run: RunOptions,
+    pub iterations: usize,
+    pub baseline_path: String,
+    pub threshold: f64,
+    pub save: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyOptions {
+    pub run: RunOptions,
+    pub answers_path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FramesOptions {
+    pub run: RunOptions,
+    pub part: Part,
+    pub format: FrameFormat,
+    pub output: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GridBenchOptions {
+    pub sizes: Vec<usize>,
+    /// Available cores when none
+    pub threads: Option<usize>,
+    pub iterations: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExpensesOptions {
+    pub run: RunOptions,
+    pub target: i64,
+    pub k: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PoliciesOptions {
+    pub run: RunOptions,
+    pub rules_path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CubesOptions {
+    /// Path of the 2D slice, - for stdin, or the default slice when none
+    pub input: Option<String>,
+    pub dimensions: usize,
+    pub cycles: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LifeOptions {
+    /// Path of the 2D slice, - for stdin, or the default slice when none
+    pub input: Option<String>,
+    /// Checked to parse as a `Neighbourhood<bool>`
+    pub neighbourhood: String,
+    pub edges: Edges,
+    pub generations: usize,
+    pub transpose: bool,
+    pub quarter_turns: usize,
+}
+
+impl RunOptions {
+    pub fn get_input(&self, day: usize) -> Input {
+        match (self.example, self.inputs.get(&day)) {
+            (Some(index), _) => Input::Example(index),
+            (None, Some(path)) if path == "-" => Input::Stdin,
+            (None, Some(path)) => Input::File(path.clone()),
+            (None, None) => Input::File(format!("src/day{}/data.txt", day)),
+        }
+    }
+}
+
+impl Command {
+    pub fn from_args<I: Iterator<Item=String>>(args: I, available_days: &[usize]) -> Result<Self, GenericError> {
+        let mut args = args.peekable();
+        let sub_command = match args.peek().map(|arg| arg.as_str()) {
+            Some("run") => Some(SubCommand::Run),
+            Some("bench") => Some(SubCommand::Bench),
+            Some("verify") => Some(SubCommand::Verify),
+            Some("frames") => Some(SubCommand::Frames),
+            Some("bench-grid") => Some(SubCommand::BenchGrid),
+            Some("expenses") => Some(SubCommand::Expenses),
+            Some("policies") => Some(SubCommand::Policies),
+            Some("audit") => Some(SubCommand::Audit),
+            Some("passports") => Some(SubCommand::Passports),
+            Some("cubes") => Some(SubCommand::Cubes),
+            Some("life") => Some(SubCommand::Life),
+            Some("help") | Some("--help") | Some("-h") => return Ok(Command::Help),
+            _ => None,
+        };
+        if sub_command.is_some() {
+            args.next();
+        }
+        let sub_command = sub_command.unwrap_or(SubCommand::Run);
+        let is_bench = sub_command == SubCommand::Bench;
+        let is_grid_bench = sub_command == SubCommand::BenchGrid;
+
+        let mut days = vec![];
+        let mut parts = None;
+        let mut raw_inputs = vec![];
+        let mut load_mode = LoadMode::Strict;
+        let mut format = Format::Text;
+        let mut example = None;
+        let mut iterations = 10;
+        let mut baseline_path = DEFAULT_BASELINE_PATH.to_string();
+        let mut threshold = 20.0;
+        let mut save = false;
+        let mut answers_path = DEFAULT_ANSWERS_PATH.to_string();
+        let mut frame_format = FrameFormat::Text;
+        let mut output = None;
+        let mut sizes = vec![];
+        let mut threads = None;
+        let mut target = crate::day1::TARGET;
+        let mut k = 2;
+        let mut rules_path = DEFAULT_POLICIES_PATH.to_string();
+        let mut schema_path = None;
+        let mut dimensions = 3;
+        let mut cycles = 6;
+        let mut neighbourhood = "moore".to_string();
+        let mut edges = Edges::Bounded;
+        let mut generations = 4;
+        let mut transpose = false;
+        let mut quarter_turns = 0;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--day" | "-d" => days.extend(parse_days(&next_value(&mut args, &arg)?)?),
+                "--part" | "-p" => parts = Some(parse_parts(&next_value(&mut args, &arg)?)?),
+                "--input" | "-i" => raw_inputs.push(next_value(&mut args, &arg)?),
+                "--example" | "-e" => {
+                    let number = match args.peek().and_then(|value| value.parse::<usize>().ok()) {
+                        Some(number) => {
+                            args.next();
+                            number
+                        }
+                        None => 1,
+                    };
+                    if number == 0 {
+                        return Err(GenericError::Usage("Examples are numbered from 1".to_string()));
+                    }
+                    example = Some(number - 1);
+                }
+                "--lenient" if matches!(sub_command, SubCommand::Run | SubCommand::Audit | SubCommand::Passports) => load_mode = LoadMode::Lenient,
+                "--format" | "-f" if sub_command == SubCommand::Run || sub_command == SubCommand::Audit =>
+                    format = parse_next(&mut args, &arg)?,
+                "--iterations" | "-n" if is_bench || is_grid_bench => iterations = parse_next(&mut args, &arg)?,
+                "--baseline" if is_bench => baseline_path = next_value(&mut args, &arg)?,
+                "--threshold" if is_bench => threshold = parse_next(&mut args, &arg)?,
+                "--save" if is_bench => save = true,
+                "--answers" if sub_command == SubCommand::Verify => answers_path = next_value(&mut args, &arg)?,
+                "--format" | "-f" if sub_command == SubCommand::Frames => frame_format = parse_next(&mut args, &arg)?,
+                "--output" | "-o" if sub_command == SubCommand::Frames => output = Some(next_value(&mut args, &arg)?),
+                "--size" if is_grid_bench => sizes.push(parse_next(&mut args, &arg)?),
+                "--threads" if is_grid_bench => threads = Some(parse_next(&mut args, &arg)?),
+                "--target" | "-t" if sub_command == SubCommand::Expenses => target = parse_next(&mut args, &arg)?,
+                "-k" if sub_command == SubCommand::Expenses => k = parse_next(&mut args, &arg)?,
+                "--rules" | "-r" if sub_command == SubCommand::Policies => rules_path = next_value(&mut args, &arg)?,
+                "--schema" | "-s" if sub_command == SubCommand::Run || sub_command == SubCommand::Passports =>
+                    schema_path = Some(next_value(&mut args, &arg)?),
+                "--dimensions" if sub_command == SubCommand::Cubes => dimensions = parse_next(&mut args, &arg)?,
+                "--cycles" if sub_command == SubCommand::Cubes => cycles = parse_next(&mut args, &arg)?,
+                "--neighbourhood" if sub_command == SubCommand::Life => neighbourhood = next_value(&mut args, &arg)?,
+                "--edges" if sub_command == SubCommand::Life => edges = parse_next(&mut args, &arg)?,
+                "--generations" if sub_command == SubCommand::Life => generations = parse_next(&mut args, &arg)?,
+                "--transpose" if sub_command == SubCommand::Life => transpose = true,
+                "--rotate" if sub_command == SubCommand::Life => quarter_turns = parse_next(&mut args, &arg)?,
+                "--help" | "-h" => return Ok(Command::Help),
+                "-" => raw_inputs.push(arg),
+                _ => return Err(GenericError::Usage(format!("Unknown argument '{}'", arg))),
+            }
+        }
+
+        if is_grid_bench {
+            if !days.is_empty() || parts.is_some() || !raw_inputs.is_empty() || example.is_some() {
+                return Err(GenericError::Usage("bench-grid only runs on random grids".to_string()));
+            }
+            if iterations == 0 || threads == Some(0) {
+                return Err(GenericError::Usage("At least one iteration and one thread are needed".to_string()));
+            }
+            if sizes.is_empty() {
+                sizes = DEFAULT_GRID_SIZES.to_vec();
+            }
+        }
+
+        if sub_command == SubCommand::Cubes {
+            if !days.is_empty() || parts.is_some() || example.is_some() || raw_inputs.len() > 1 {
+                return Err(GenericError::Usage("cubes only runs on a single 2D slice".to_string()));
+            }
+            if !CUBE_DIMENSIONS.contains(&dimensions) {
+                return Err(GenericError::Usage(format!("Cubes can be lifted to 2, 3 or 4 dimensions, not {}", dimensions)));
+            }
+            return Ok(Command::Cubes(CubesOptions { input: raw_inputs.pop(), dimensions, cycles }));
+        }
+
+        if sub_command == SubCommand::Life {
+            if !days.is_empty() || parts.is_some() || example.is_some() || raw_inputs.len() > 1 {
+                return Err(GenericError::Usage("life only runs on a single 2D slice".to_string()));
+            }
+            neighbourhood.parse::<Neighbourhood<bool>>()?;
+            return Ok(Command::Life(LifeOptions { input: raw_inputs.pop(), neighbourhood, edges, generations, transpose, quarter_turns }));
+        }
+
+        if sub_command == SubCommand::Frames {
+            if days.iter().any(|day| *day != FRAMES_DAY) {
+                return Err(GenericError::Usage(format!("Generations can only be rendered for day {}", FRAMES_DAY)));
+            }
+            days = vec![FRAMES_DAY];
+        }
+        if sub_command == SubCommand::Expenses {
+            if days.iter().any(|day| *day != EXPENSES_DAY) || parts.is_some() {
+                return Err(GenericError::Usage(format!("Only the day {} expense report can be analysed", EXPENSES_DAY)));
+            }
+            if k == 0 {
+                return Err(GenericError::Usage("Sets of expenses need at least one expense".to_string()));
+            }
+            days = vec![EXPENSES_DAY];
+        }
+        if sub_command == SubCommand::Policies {
+            if days.iter().any(|day| *day != PASSWORDS_DAY) || parts.is_some() {
+                return Err(GenericError::Usage(format!("Policies only apply to the day {} passwords", PASSWORDS_DAY)));
+            }
+            days = vec![PASSWORDS_DAY];
+        }
+        if sub_command == SubCommand::Audit {
+            if days.iter().any(|day| *day != PASSWORDS_DAY) || parts.is_some() {
+                return Err(GenericError::Usage(format!("Only the day {} passwords can be audited", PASSWORDS_DAY)));
+            }
+            if format == Format::Csv {
+                return Err(GenericError::Usage("Audits are written as text or JSON".to_string()));
+            }
+            days = vec![PASSWORDS_DAY];
+        }
+        if sub_command == SubCommand::Passports {
+            if days.iter().any(|day| *day != PASSPORTS_DAY) || parts.is_some() {
+                return Err(GenericError::Usage(format!("Only the day {} passports can be diagnosed", PASSPORTS_DAY)));
+            }
+            days = vec![PASSPORTS_DAY];
+        }
+        if days.is_empty() {
+            days = available_days.to_vec();
+        }
+        days.sort_unstable();
+        days.dedup();
+
+        if let Some(day) = days.iter().find(|day| !available_days.contains(day)) {
+            return Err(GenericError::Usage(format!("Day {} is not available", day)));
+        }
+
+        let mut inputs = HashMap::default();
+        for raw_input in raw_inputs {
+            let (day, path) = parse_input(&raw_input, &days)?;
+            if let Some(previous) = inputs.insert(day, path) {
+                return Err(GenericError::Usage(format!("Day {} has two inputs, '{}' and '{}'", day, previous, raw_input)));
+            }
+        }
+
+        if schema_path.is_some() && !days.contains(&PASSPORTS_DAY) {
+            return Err(GenericError::Usage(format!("A schema only applies to the day {} passports", PASSPORTS_DAY)));
+        }
+
+        if example.is_some() && !inputs.is_empty() {
+            return Err(GenericError::Usage("An example cannot be used with an input file".to_string()));
+        }
+
+        let default_parts = match sub_command {
+            SubCommand::Frames => vec![Part::One],
+            _ => vec![Part::One, Part::Two],
+        };
+        let parts = parts.unwrap_or(default_parts);
+
+        let run = RunOptions { days, parts, inputs, load_mode, format, example, schema_path };
+        match sub_command {
+            SubCommand::Run => Ok(Command::Run(run)),
+            SubCommand::Bench if iterations == 0 =>
+                Err(GenericError::Usage("At least one iteration is needed".to_string())),
+            SubCommand::Bench => Ok(Command::Bench(BenchOptions { run, iterations, baseline_path, threshold, save })),
+            SubCommand::Verify => Ok(Command::Verify(VerifyOptions { run, answers_path })),
+            SubCommand::BenchGrid => Ok(Command::BenchGrid(GridBenchOptions { sizes, threads, iterations })),
+            SubCommand::Expenses => Ok(Command::Expenses(ExpensesOptions { run, target, k })),
+            SubCommand::Policies => Ok(Command::Policies(PoliciesOptions { run, rules_path })),
+            SubCommand::Audit => Ok(Command::Audit(run)),
+            SubCommand::Passports => Ok(Command::Passports(run)),
+            SubCommand::Cubes | SubCommand::Life => unreachable!("Slice options are returned before the days are selected"),
+            SubCommand::Frames => match run.parts.as_slice() {
+                [part] => Ok(Command::Frames(FramesOptions { part: *part, run, format: frame_format, output })),
+                _ => Err(GenericError::Usage("Generations are rendered for a single part".to_string())),
+            },
+        }
+    }
+}
+
+fn next_value<I: Iterator<Item=String>>(args: &mut I, flag: &str) -> Result<String, GenericError> {
+    args.next().ok_or_else(|| GenericError::Usage(format!("Missing value for {}", flag)))
+}
+
+fn parse_next<I: Iterator<Item=String>, T: FromStr>(args: &mut I, flag: &str) -> Result<T, GenericError>
+    where T::Err: Display
+{
+    let value = next_value(args, flag)?;
+    value.parse().map_err(|err| GenericError::Usage(format!("Invalid value '{}' for {}: {}", value, flag, err)))
+}
+
+fn parse_number(value: &str) -> Result<usize, GenericError> {
+    value.trim().parse().map_err(|_| GenericError::Usage(format!("'{}' is not a day", value.trim())))
+}
+
+/// Parse a day selection such as "7", "1-5" or "1,3,8-11"
+pub fn parse_days(spec: &str) -> Result<Vec<usize>, GenericError> {
+    let mut days = vec![];
+    for item in spec.split(',').map(|item| item.trim()) {
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let start = parse_number(start)?;
+                let end = parse_number(end)?;
+                if start > end {
+                    return Err(GenericError::Usage(format!("Invalid day range '{}'", item)));
+                }
+                days.extend(start..=end);
+            }
+            None => days.push(parse_number(item)?),
+        }
+    }
+
+    Ok(days)
+}
+
+pub fn parse_parts(spec: &str) -> Result<Vec<Part>, GenericError> {
+    match spec {
+        "1" => Ok(vec![Part::One]),
+        "2" => Ok(vec![Part::Two]),
+        "both" | "1,2" => Ok(vec![Part::One, Part::Two]),
+        _ => Err(GenericError::Usage(format!("Invalid part '{}'", spec))),
+    }
+}
+
+fn parse_input(spec: &str, days: &[usize]) -> Result<(usize, String), GenericError> {
+    if let Some((day, path)) = spec.split_once('=') {
+        if let Ok(day) = day.parse() {
+            if !days.contains(&day) {
+                return Err(GenericError::Usage(format!("Input '{}' is for day {}, which is not selected", spec, day)));
+            }
+            return Ok((day, path.to_string()));
+        }
+    }
+
+    match days {
+        [day] => Ok((*day, spec.to_string())),
+        _ => Err(GenericError::Usage(format!("Input '{}' must be prefixed with its day, as in <day>=<path>", spec))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{Command, LifeOptions, parse_days};
+    use crate::utils::GenericError;
+    use crate::daily_challenge::{Part, Input};
+    use crate::utils::LoadMode;
+    use crate::output::Format;
+    use crate::frames::FrameFormat;
+    use crate::grid::Edges;
+
+    fn args(input: &str) -> impl Iterator<Item=String> + '_ {
+        input.split_whitespace().map(|arg| arg.to_string())
+    }
+
+    #[test]
+    fn it_parses_day_selections() {
+        assert_eq!(parse_days("7").unwrap(), vec![7]);
+        assert_eq!(parse_days("1-3").unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_days("1,3,8-10").unwrap(), vec![1, 3, 8, 9, 10]);
+        assert!(matches!(parse_days("5-2"), Err(GenericError::Usage(_))));
+        assert!(matches!(parse_days("x"), Err(GenericError::Usage(_))));
+        assert!(matches!(parse_days("1-x"), Err(GenericError::Usage(_))));
+    }
+
+    #[test]
+    fn it_reports_usage_errors() {
+        let available_days: Vec<usize> = (1..=11).collect();
+        for invalid in &["--day x", "--day 5-2", "--day 12", "bench --iterations x", "bench --threshold fast",
+                         "--format xml", "--unknown", "--part"] {
+            assert!(matches!(Command::from_args(args(invalid), &available_days), Err(GenericError::Usage(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn it_parses_run_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("run --day 7 --part 2 --input path.txt"), &available_days).unwrap() {
+            Command::Run(options) => {
+                assert_eq!(options.days, vec![7]);
+                assert_eq!(options.parts, vec![Part::Two]);
+                assert_eq!(options.get_input(7), Input::File("path.txt".to_string()));
+                assert_eq!(options.load_mode, LoadMode::Strict);
+                assert_eq!(options.format, Format::Text);
+            }
+            _ => panic!("Expected a run command"),
+        }
+
+        match Command::from_args(args("--day 1-2 --day 9 --input 2=other.txt --lenient --format csv"), &available_days).unwrap() {
+            Command::Run(options) => {
+                assert_eq!(options.days, vec![1, 2, 9]);
+                assert_eq!(options.parts, vec![Part::One, Part::Two]);
+                assert_eq!(options.get_input(1), Input::File("src/day1/data.txt".to_string()));
+                assert_eq!(options.get_input(2), Input::File("other.txt".to_string()));
+                assert_eq!(options.load_mode, LoadMode::Lenient);
+                assert_eq!(options.format, Format::Csv);
+            }
+            _ => panic!("Expected a run command"),
+        }
+
+        match Command::from_args(args(""), &available_days).unwrap() {
+            Command::Run(options) => assert_eq!(options.days, available_days),
+            _ => panic!("Expected a run command"),
+        }
+
+        assert!(Command::from_args(args("--day 1-2 --input path.txt"), &available_days).is_err());
+        assert!(matches!(Command::from_args(args("--day 1 --input 2=foo.txt"), &available_days), Err(GenericError::Usage(_))));
+        assert!(matches!(Command::from_args(args("--day 9 --input a.txt --input -"), &available_days), Err(GenericError::Usage(_))));
+        assert!(Command::from_args(args("--day 1-2 --input 2=a.txt --input 2=b.txt"), &available_days).is_err());
+        assert!(Command::from_args(args("--day 12"), &available_days).is_err());
+        assert!(Command::from_args(args("--part 3"), &available_days).is_err());
+        assert!(Command::from_args(args("run --save"), &available_days).is_err());
+        assert!(Command::from_args(args("run --format xml"), &available_days).is_err());
+
+        match Command::from_args(args("--day 10 --example 2"), &available_days).unwrap() {
+            Command::Run(options) => assert_eq!(options.get_input(10), Input::Example(1)),
+            _ => panic!("Expected a run command"),
+        }
+        match Command::from_args(args("--example --day 10"), &available_days).unwrap() {
+            Command::Run(options) => assert_eq!(options.get_input(10), Input::Example(0)),
+            _ => panic!("Expected a run command"),
+        }
+        assert!(Command::from_args(args("--day 10 --example 0"), &available_days).is_err());
+
+        match Command::from_args(args("run --day 9 -"), &available_days).unwrap() {
+            Command::Run(options) => assert_eq!(options.get_input(9), Input::Stdin),
+            _ => panic!("Expected a run command"),
+        }
+        assert!(Command::from_args(args("run --day 8-9 -"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_bench_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("bench --day 8 --part 2 --iterations 5 --save"), &available_days).unwrap() {
+            Command::Bench(options) => {
+                assert_eq!(options.run.days, vec![8]);
+                assert_eq!(options.run.parts, vec![Part::Two]);
+                assert_eq!(options.iterations, 5);
+                assert_eq!(options.baseline_path, "bench_baseline.tsv");
+                assert!(options.save);
+            }
+            _ => panic!("Expected a bench command"),
+        }
+
+        assert!(Command::from_args(args("bench --iterations 0"), &available_days).is_err());
+        assert!(Command::from_args(args("bench --lenient"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_verify_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("verify --day 10 --answers answers.toml"), &available_days).unwrap() {
+            Command::Verify(options) => {
+                assert_eq!(options.run.days, vec![10]);
+                assert_eq!(options.answers_path, "answers.toml");
+            }
+            _ => panic!("Expected a verify command"),
+        }
+
+        match Command::from_args(args("verify"), &available_days).unwrap() {
+            Command::Verify(options) => assert_eq!(options.answers_path, "src/answers.toml"),
+            _ => panic!("Expected a verify command"),
+        }
+
+        assert!(Command::from_args(args("run --answers answers.toml"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_frames_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("frames --part 2 --format gif --output seats.gif --input grid.txt"), &available_days).unwrap() {
+            Command::Frames(options) => {
+                assert_eq!(options.run.days, vec![11]);
+                assert_eq!(options.part, Part::Two);
+                assert_eq!(options.format, FrameFormat::Gif);
+                assert_eq!(options.output, Some("seats.gif".to_string()));
+                assert_eq!(options.run.get_input(11), Input::File("grid.txt".to_string()));
+            }
+            _ => panic!("Expected a frames command"),
+        }
+
+        match Command::from_args(args("frames"), &available_days).unwrap() {
+            Command::Frames(options) => {
+                assert_eq!(options.part, Part::One);
+                assert_eq!(options.format, FrameFormat::Text);
+            }
+            _ => panic!("Expected a frames command"),
+        }
+
+        assert!(Command::from_args(args("frames --part both"), &available_days).is_err());
+        assert!(Command::from_args(args("frames --day 3"), &available_days).is_err());
+        assert!(Command::from_args(args("frames --format json"), &available_days).is_err());
+        assert!(Command::from_args(args("run --output out.txt"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_grid_bench_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("bench-grid --size 500 --size 1000 --threads 4 -n 3"), &available_days).unwrap() {
+            Command::BenchGrid(options) => {
+                assert_eq!(options.sizes, vec![500, 1000]);
+                assert_eq!(options.threads, Some(4));
+                assert_eq!(options.iterations, 3);
+            }
+            _ => panic!("Expected a grid bench command"),
+        }
+
+        match Command::from_args(args("bench-grid"), &available_days).unwrap() {
+            Command::BenchGrid(options) => {
+                assert_eq!(options.sizes, vec![1000, 2000]);
+                assert_eq!(options.threads, None);
+            }
+            _ => panic!("Expected a grid bench command"),
+        }
+
+        assert!(Command::from_args(args("bench-grid --day 11"), &available_days).is_err());
+        assert!(Command::from_args(args("bench-grid --threads 0"), &available_days).is_err());
+        assert!(Command::from_args(args("bench --size 10"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_expenses_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("expenses --target -150 -k 4 --input report.txt"), &available_days).unwrap() {
+            Command::Expenses(options) => {
+                assert_eq!(options.target, -150);
+                assert_eq!(options.k, 4);
+                assert_eq!(options.run.days, vec![1]);
+                assert_eq!(options.run.get_input(1), Input::File("report.txt".to_string()));
+            }
+            _ => panic!("Expected an expenses command"),
+        }
+
+        match Command::from_args(args("expenses --example"), &available_days).unwrap() {
+            Command::Expenses(options) => {
+                assert_eq!((options.target, options.k), (2020, 2));
+                assert_eq!(options.run.get_input(1), Input::Example(0));
+            }
+            _ => panic!("Expected an expenses command"),
+        }
+
+        assert!(Command::from_args(args("expenses --day 2"), &available_days).is_err());
+        assert!(Command::from_args(args("expenses --part 1"), &available_days).is_err());
+        assert!(Command::from_args(args("expenses --target x"), &available_days).is_err());
+        assert!(matches!(Command::from_args(args("expenses -k 0"), &available_days), Err(GenericError::Usage(_))));
+        assert!(Command::from_args(args("run -k 3"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_policies_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("policies --rules rules.txt -"), &available_days).unwrap() {
+            Command::Policies(options) => {
+                assert_eq!(options.rules_path, "rules.txt");
+                assert_eq!(options.run.get_input(2), Input::Stdin);
+            }
+            _ => panic!("Expected a policies command"),
+        }
+
+        match Command::from_args(args("policies"), &available_days).unwrap() {
+            Command::Policies(options) => assert_eq!(options.rules_path, "src/day2/policies.txt"),
+            _ => panic!("Expected a policies command"),
+        }
+
+        assert!(Command::from_args(args("policies --day 1"), &available_days).is_err());
+        assert!(Command::from_args(args("run --rules rules.txt"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_audit_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("audit --format json --example --lenient"), &available_days).unwrap() {
+            Command::Audit(options) => {
+                assert_eq!(options.format, Format::Json);
+                assert_eq!(options.days, vec![2]);
+                assert_eq!(options.get_input(2), Input::Example(0));
+                assert_eq!(options.load_mode, LoadMode::Lenient);
+            }
+            _ => panic!("Expected an audit command"),
+        }
+
+        assert!(Command::from_args(args("audit --format csv"), &available_days).is_err());
+        match Command::from_args(args("audit --day 3"), &available_days) {
+            Err(err) => assert_eq!(err.to_string(), "Only the day 2 passwords can be audited"),
+            _ => panic!("Expected a usage error"),
+        }
+    }
+
+    #[test]
+    fn it_parses_passports_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("passports --schema schema.json --example 2"), &available_days).unwrap() {
+            Command::Passports(options) => {
+                assert_eq!(options.schema_path, Some("schema.json".to_string()));
+                assert_eq!(options.days, vec![4]);
+                assert_eq!(options.get_input(4), Input::Example(1));
+            }
+            _ => panic!("Expected a passports command"),
+        }
+
+        match Command::from_args(args("passports --lenient"), &available_days).unwrap() {
+            Command::Passports(options) => {
+                assert_eq!(options.schema_path, None);
+                assert_eq!(options.load_mode, LoadMode::Lenient);
+            }
+            _ => panic!("Expected a passports command"),
+        }
+
+        match Command::from_args(args("run --day 3-4 --schema schema.toml"), &available_days).unwrap() {
+            Command::Run(options) => assert_eq!(options.schema_path, Some("schema.toml".to_string())),
+            _ => panic!("Expected a run command"),
+        }
+
+        assert!(Command::from_args(args("passports --day 2"), &available_days).is_err());
+        assert!(matches!(Command::from_args(args("run --day 2 --schema schema.toml"), &available_days), Err(GenericError::Usage(_))));
+        assert!(Command::from_args(args("verify --schema schema.toml"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_cubes_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("cubes --dimensions 4 --cycles 2 --input slice.txt"), &available_days).unwrap() {
+            Command::Cubes(options) => {
+                assert_eq!(options.input, Some("slice.txt".to_string()));
+                assert_eq!((options.dimensions, options.cycles), (4, 2));
+            }
+            _ => panic!("Expected a cubes command"),
+        }
+
+        match Command::from_args(args("cubes"), &available_days).unwrap() {
+            Command::Cubes(options) => assert_eq!((options.input, options.dimensions, options.cycles), (None, 3, 6)),
+            _ => panic!("Expected a cubes command"),
+        }
+
+        assert!(matches!(Command::from_args(args("cubes --dimensions 5"), &available_days), Err(GenericError::Usage(_))));
+        assert!(Command::from_args(args("cubes --day 11"), &available_days).is_err());
+        assert!(Command::from_args(args("run --cycles 2"), &available_days).is_err());
+    }
+
+    #[test]
+    fn it_parses_life_command() {
+        let available_days: Vec<usize> = (1..=11).collect();
+
+        match Command::from_args(args("life --neighbourhood 1,0;0,1 --edges toroidal --generations 9 --transpose --rotate 3 -"), &available_days).unwrap() {
+            Command::Life(options) => assert_eq!(options, LifeOptions {
+                input: Some("-".to_string()),
+                neighbourhood: "1,0;0,1".to_string(),
+                edges: Edges::Toroidal,
+                generations: 9,
+                transpose: true,
+                quarter_turns: 3,
+            }),
+            _ => panic!("Expected a life command"),
+        }
+
+        match Command::from_args(args("life"), &available_days).unwrap() {
+            Command::Life(options) => {
+                assert_eq!((options.neighbourhood.as_str(), options.edges, options.generations), ("moore", Edges::Bounded, 4));
+                assert_eq!((options.transpose, options.quarter_turns), (false, 0));
+            }
+            _ => panic!("Expected a life command"),
+        }
+
+        assert!(matches!(Command::from_args(args("life --neighbourhood hex"), &available_days), Err(GenericError::Usage(_))));
+        assert!(matches!(Command::from_args(args("life --edges sphere"), &available_days), Err(GenericError::Usage(_))));
+        assert!(Command::from_args(args("life --example"), &available_days).is_err());
+        assert!(Command::from_args(args("cubes --edges wrap"), &available_days).is_err());
+        assert!(Command::from_args(args("cubes --rotate 1"), &available_days).is_err());
+    }
+}
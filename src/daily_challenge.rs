@@ -1,36 +1,308 @@
-use crate::utils::{load_data, GenericError};
+use crate::utils::{load_data, parse_data, read_data, GenericError, LoadMode, Records};
+use crate::bench::{Measure, Phase, Stats};
 use std::str::FromStr;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+use std::io::Read;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn get_num(&self) -> usize {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_num())
+    }
+}
+
+/// Where the input of a day comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    File(String),
+    Stdin,
+    Text(String),
+    /// One of the examples of the day, by its 0-based index
+    Example(usize),
+}
+
+impl Input {
+    /// Read stdin up front, so that the input can be loaded several times
+    pub fn buffered(self) -> Result<Self, GenericError> {
+        match self {
+            Input::Stdin => {
+                let mut contents = String::new();
+                std::io::stdin().read_to_string(&mut contents)?;
+                Ok(Input::Text(contents))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::File(path) => write!(f, "{}", path),
+            Input::Stdin => write!(f, "stdin"),
+            Input::Text(_) => write!(f, "text"),
+            Input::Example(index) => write!(f, "example {}", index + 1),
+        }
+    }
+}
+
+/// A small input from the puzzle statement, with the answers it gives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    pub input: &'static str,
+    pub part_1: Option<&'static str>,
+    pub part_2: Option<&'static str>,
+}
+
+impl Example {
+    pub fn get_expected(&self, part: Part) -> Option<&'static str> {
+        match part {
+            Part::One => self.part_1,
+            Part::Two => self.part_2,
+        }
+    }
+}
+
+/// Solution of a part: the bare value to submit, with an optional human explanation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub value: String,
+    pub explanation: Option<String>,
+}
+
+impl Answer {
+    pub fn new<T: ToString>(value: T) -> Self {
+        Answer { value: value.to_string(), explanation: None }
+    }
+
+    pub fn with_explanation(mut self, explanation: String) -> Self {
+        self.explanation = Some(explanation);
+        self
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.explanation {
+            Some(explanation) => write!(f, "{} ({})", self.value, explanation),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+pub struct PartReport {
+    pub part: Part,
+    pub answer: Answer,
+    pub duration: Duration,
+}
+
+/// Results of a day with the time spent in each phase
+pub struct DayReport {
+    pub day: usize,
+    pub warnings: Vec<String>,
+    pub parse_duration: Duration,
+    pub parts: Vec<PartReport>,
+}
+
+impl Display for DayReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# Day {}", self.day)?;
+        for warning in &self.warnings {
+            writeln!(f, " - Skipped {}", warning)?;
+        }
+        writeln!(f, " - Parse : {:?}", self.parse_duration)?;
+        for part in &self.parts {
+            writeln!(f, " - Part {} : {} in {:?}", part.part, part.answer, part.duration)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn timed<T, F>(mut action: F) -> Result<(T, Duration), GenericError>
+    where F: FnMut() -> Result<T, GenericError>
+{
+    let start = Instant::now();
+    let result = action()?;
+    Ok((result, start.elapsed()))
+}
 
 pub trait DailyChallenge {
     type Data: FromStr;
-    type Wrapper: From<Vec<Self::Data>>;
+    /// Records of the input, gathered in a type that may reject some combinations of records
+    type Wrapper: TryFrom<Vec<Self::Data>>;
 
     fn get_day_num(&self) -> usize;
 
-    fn load_data(&self, file_path: &str) -> Result<Self::Wrapper, GenericError>
-        where <Self::Data as std::str::FromStr>::Err: std::error::Error
+    /// Pattern between two records of the input file
+    fn get_split_pattern(&self) -> &str { "\n" }
+
+    /// Examples of the puzzle statement, with their expected answers
+    fn get_examples(&self) -> Vec<Example> { vec![] }
+
+    /// The challenge solving an example, when the statement gives it other settings than the input
+    fn get_example_challenge(&self, _index: usize) -> Option<Self> where Self: Sized { None }
+
+    /// The example of the statement at a 0-based index
+    fn get_example(&self, index: usize) -> Result<Example, GenericError> {
+        self.get_examples().into_iter().nth(index)
+            .ok_or_else(|| GenericError::Usage(format!("Day {} has no {}", self.get_day_num(), Input::Example(index))))
+    }
+
+    /// Load the records of the input with their locations, also returning the records skipped in lenient mode
+    fn load_records(&self, input: &Input, mode: LoadMode) -> Result<(Records<Self::Data>, Vec<String>), GenericError>
+        where <Self::Data as std::str::FromStr>::Err: std::error::Error + 'static
     {
-        let data: Vec<Self::Data> = load_data(file_path, "\n")?;
-        Ok(data.into())
+        let (records, warnings) = match input {
+            Input::File(file_path) => load_data(file_path, self.get_split_pattern(), mode)?,
+            Input::Stdin => read_data(std::io::stdin().lock(), &input.to_string(), self.get_split_pattern(), mode)?,
+            Input::Text(contents) => parse_data(contents, &input.to_string(), self.get_split_pattern(), mode)?,
+            Input::Example(index) => parse_data(self.get_example(*index)?.input, &input.to_string(), self.get_split_pattern(), mode)?,
+        };
+        Ok((records, warnings.into_iter().map(|warning| warning.to_string()).collect()))
     }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError>;
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError>;
+    /// Load the input, also returning the records skipped in lenient mode
+    fn load_data(&self, input: &Input, mode: LoadMode) -> Result<(Self::Wrapper, Vec<String>), GenericError>
+        where <Self::Data as std::str::FromStr>::Err: std::error::Error + 'static,
+              GenericError: From<<Self::Wrapper as TryFrom<Vec<Self::Data>>>::Error>
+    {
+        let (records, warnings) = self.load_records(input, mode)?;
+        let data: Vec<Self::Data> = records.into_iter().map(|(data, _)| data).collect();
+        Ok((Self::Wrapper::try_from(data)?, warnings))
+    }
 
-    fn solve(&self, file_path: &str) -> Result<String, GenericError>
-        where <Self::Data as std::str::FromStr>::Err: std::error::Error
+    /// Strictly parse an in-memory input
+    #[cfg(test)]
+    fn parse_input(&self, contents: &str) -> Result<Self::Wrapper, GenericError>
+        where <Self::Data as std::str::FromStr>::Err: std::error::Error + 'static,
+              GenericError: From<<Self::Wrapper as TryFrom<Vec<Self::Data>>>::Error>
     {
-        let mut result = String::new();
-        result.push_str(format!("# Day {}", self.get_day_num()).as_str());
-        result.push_str("\n");
+        let (data, _) = self.load_data(&Input::Text(contents.to_string()), LoadMode::Strict)?;
+        Ok(data)
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError>;
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError>;
+
+    fn solve_part(&self, data: &Self::Wrapper, part: Part) -> Result<Answer, GenericError> {
+        match part {
+            Part::One => self.solve_part_1(data),
+            Part::Two => self.solve_part_2(data),
+        }
+    }
 
-        let data = self.load_data(file_path)?;
+    fn run(&self, input: &Input, parts: &[Part], mode: LoadMode) -> Result<DayReport, GenericError>
+        where <Self::Data as std::str::FromStr>::Err: std::error::Error + 'static,
+              GenericError: From<<Self::Wrapper as TryFrom<Vec<Self::Data>>>::Error>
+    {
+        let ((data, warnings), parse_duration) = timed(|| self.load_data(input, mode))?;
+
+        let mut part_reports = vec![];
+        for part in parts {
+            let (answer, duration) = timed(|| self.solve_part(&data, *part))?;
+            part_reports.push(PartReport { part: *part, answer, duration });
+        }
+
+        Ok(DayReport {
+            day: self.get_day_num(),
+            warnings,
+            parse_duration,
+            parts: part_reports,
+        })
+    }
 
-        result.push_str(format!(" - Part 1 : {}", self.solve_part_1(&data)?).as_str());
-        result.push_str("\n");
-        result.push_str(format!(" - Part 2 : {}", self.solve_part_2(&data)?).as_str());
-        result.push_str("\n");
+    /// Run each phase `iterations` times, parts are solved on data parsed once
+    fn bench(&self, input: &Input, parts: &[Part], iterations: usize) -> Result<Vec<Measure>, GenericError>
+        where <Self::Data as std::str::FromStr>::Err: std::error::Error + 'static,
+              GenericError: From<<Self::Wrapper as TryFrom<Vec<Self::Data>>>::Error>
+    {
+        let mut parse_durations = vec![];
+        for _ in 0..iterations {
+            let (_, duration) = timed(|| self.load_data(input, LoadMode::Strict))?;
+            parse_durations.push(duration);
+        }
+
+        let mut measures = vec![Measure {
+            day: self.get_day_num(),
+            phase: Phase::Parse,
+            stats: Stats::from_durations(parse_durations),
+        }];
+
+        let (data, _) = self.load_data(input, LoadMode::Strict)?;
+        for part in parts {
+            let mut part_durations = vec![];
+            for _ in 0..iterations {
+                let (_, duration) = timed(|| self.solve_part(&data, *part))?;
+                part_durations.push(duration);
+            }
+            measures.push(Measure {
+                day: self.get_day_num(),
+                phase: Phase::Part(*part),
+                stats: Stats::from_durations(part_durations),
+            });
+        }
+
+        Ok(measures)
+    }
+}
+
+/// Object-safe view of a `DailyChallenge`, so challenges with different
+/// `Data`/`Wrapper` types can be stored side by side as trait objects
+pub trait Challenge {
+    fn get_day_num(&self) -> usize;
+    fn get_examples(&self) -> Vec<Example>;
+    fn run(&self, input: &Input, parts: &[Part], mode: LoadMode) -> Result<DayReport, GenericError>;
+    fn bench(&self, input: &Input, parts: &[Part], iterations: usize) -> Result<Vec<Measure>, GenericError>;
+}
+
+impl<T> Challenge for T
+    where T: DailyChallenge,
+          <T::Data as std::str::FromStr>::Err: std::error::Error + 'static,
+          GenericError: From<<T::Wrapper as TryFrom<Vec<T::Data>>>::Error>
+{
+    fn get_day_num(&self) -> usize {
+        DailyChallenge::get_day_num(self)
+    }
+
+    fn get_examples(&self) -> Vec<Example> {
+        DailyChallenge::get_examples(self)
+    }
+
+    fn run(&self, input: &Input, parts: &[Part], mode: LoadMode) -> Result<DayReport, GenericError> {
+        match get_example_challenge(self, input) {
+            Some(challenge) => DailyChallenge::run(&challenge, input, parts, mode),
+            None => DailyChallenge::run(self, input, parts, mode),
+        }
+    }
+
+    fn bench(&self, input: &Input, parts: &[Part], iterations: usize) -> Result<Vec<Measure>, GenericError> {
+        match get_example_challenge(self, input) {
+            Some(challenge) => DailyChallenge::bench(&challenge, input, parts, iterations),
+            None => DailyChallenge::bench(self, input, parts, iterations),
+        }
+    }
+}
 
-        return Ok(result);
+fn get_example_challenge<T: DailyChallenge>(challenge: &T, input: &Input) -> Option<T> {
+    match input {
+        Input::Example(index) => challenge.get_example_challenge(*index),
+        _ => None,
     }
 }
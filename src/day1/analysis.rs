@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use crate::day1::{find_k_sum, add_all, Amount, Expense, Search, Solutions};
+use crate::utils::GenericError;
+
+/// Expenses whose sum is the closest to a target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Closest<T> {
+    pub expenses: Vec<Expense<T>>,
+    pub sum: Expense<T>,
+    /// How far the sum is from the target, 0 on an exact match
+    pub distance: Expense<T>,
+}
+
+/// Queries over a whole expense report, for any target
+#[derive(Debug, Clone)]
+pub struct ExpenseReport<T = i64> {
+    /// Sorted in ascending order
+    expenses: Vec<Expense<T>>,
+}
+
+impl<T: Amount> ExpenseReport<T> {
+    pub fn new(mut expenses: Vec<Expense<T>>) -> Self {
+        expenses.sort();
+        ExpenseReport { expenses }
+    }
+
+    /// Whether sums can only grow when adding expenses, so that sums beyond the target can be dropped
+    fn is_non_negative(&self) -> bool {
+        self.expenses.first().is_none_or(|expense| expense.0 >= T::default())
+    }
+
+    /// Every set of `k` expenses summing to `target`, once per distinct set of values
+    pub fn find_all(&self, k: usize, target: &Expense<T>) -> Result<Solutions<T>, GenericError> {
+        find_k_sum(&self.expenses, k, target, Search::All)
+    }
+
+    /// Number of sets of `k` expenses summing to `target`, expenses of equal values counting as different ones
+    pub fn count_k_subsets(&self, k: usize, target: &Expense<T>) -> Result<u64, GenericError> {
+        if k > self.expenses.len() {
+            return Ok(0);
+        }
+
+        // ways[size] maps each sum of `size` expenses to the number of sets reaching it
+        let mut ways: Vec<HashMap<Expense<T>, u64>> = vec![HashMap::new(); k + 1];
+        ways[0].insert(Expense(T::default()), 1);
+        let is_bounded = self.is_non_negative();
+
+        for expense in &self.expenses {
+            for size in (1..=k).rev() {
+                let (smaller, larger) = ways.split_at_mut(size);
+                for (sum, count) in &smaller[size - 1] {
+                    // Without negative expenses, an overflowing sum is beyond the target like the sums above it
+                    let sum = match sum + expense {
+                        Ok(sum) if !(is_bounded && sum > *target) => sum,
+                        Err(err) if !is_bounded => return Err(err),
+                        _ => continue,
+                    };
+                    let total = larger[0].entry(sum).or_insert(0);
+                    *total = total.checked_add(*count)
+                        .ok_or_else(|| GenericError::Overflow(format!("More than {} sets of {} expenses", u64::MAX, k)))?;
+                }
+            }
+        }
+
+        Ok(ways[k].get(target).copied().unwrap_or(0))
+    }
+
+    /// One of the sets of `k` expenses whose sum is the closest to `target`, none when there are fewer expenses
+    pub fn find_closest(&self, k: usize, target: &Expense<T>) -> Result<Option<Closest<T>>, GenericError> {
+        let mut best = None;
+        find_sorted_closest(&self.expenses, k, target, &mut vec![], &Expense(T::default()), &mut best)?;
+
+        Ok(best)
+    }
+
+    /// One set of expenses of any size summing to `target`, by dynamic programming over the reachable sums
+    pub fn find_subset(&self, target: &Expense<T>) -> Result<Option<Vec<Expense<T>>>, GenericError> {
+        // Each reachable sum, with the sum it was first reached from and the index of the expense added to it
+        let mut reached: HashMap<Expense<T>, Option<(Expense<T>, usize)>> = HashMap::new();
+        reached.insert(Expense(T::default()), None);
+        let is_bounded = self.is_non_negative();
+
+        for (index, expense) in self.expenses.iter().enumerate() {
+            if reached.contains_key(target) {
+                break;
+            }
+
+            let sums: Vec<Expense<T>> = reached.keys().cloned().collect();
+            for sum in sums {
+                match &sum + expense {
+                    Ok(next) if !(is_bounded && next > *target) => {
+                        reached.entry(next).or_insert(Some((sum, index)));
+                    }
+                    Err(err) if !is_bounded => return Err(err),
+                    _ => {}
+                }
+            }
+        }
+
+        if !reached.contains_key(target) {
+            return Ok(None);
+        }
+
+        let mut subset = vec![];
+        let mut sum = target.clone();
+        while let Some(Some((previous, index))) = reached.get(&sum) {
+            subset.push(self.expenses[*index].clone());
+            sum = previous.clone();
+        }
+        subset.reverse();
+
+        Ok(Some(subset))
+    }
+}
+
+fn get_distance<T: Amount>(sum: &Expense<T>, target: &Expense<T>) -> Result<Expense<T>, GenericError> {
+    if sum >= target { sum - target } else { target - sum }
+}
+
+fn find_sorted_closest<T: Amount>(sorted: &[Expense<T>], k: usize, target: &Expense<T>, prefix: &mut Vec<Expense<T>>,
+                                  prefix_sum: &Expense<T>, best: &mut Option<Closest<T>>) -> Result<(), GenericError> {
+    let is_exact = |best: &Option<Closest<T>>| best.as_ref().is_some_and(|best| best.distance.0 == T::default());
+
+    match k {
+        0 => {
+            let distance = get_distance(prefix_sum, target)?;
+            if best.as_ref().is_none_or(|best| distance < best.distance) {
+                *best = Some(Closest { expenses: prefix.clone(), sum: prefix_sum.clone(), distance });
+            }
+        }
+        2 => {
+            let (mut low, mut high) = (0, sorted.len().saturating_sub(1));
+            while low < high && !is_exact(best) {
+                let sum = ((prefix_sum + &sorted[low])? + &sorted[high])?;
+                prefix.extend_from_slice(&[sorted[low].clone(), sorted[high].clone()]);
+                find_sorted_closest(&[], 0, target, prefix, &sum, best)?;
+                prefix.truncate(prefix.len() - 2);
+
+                if sum < *target {
+                    low += 1;
+                } else {
+                    high -= 1;
+                }
+            }
+        }
+        _ => for (index, value) in sorted.iter().enumerate() {
+            if sorted.len() - index < k || is_exact(best) {
+                break;
+            }
+            if index > 0 && sorted[index - 1] == *value {
+                continue;
+            }
+            let sum = (prefix_sum + value)?;
+            // Sums of the branch lie between these two, and both only grow with the index
+            if let (Some(best), Some(smallest)) = (best.as_ref(), add_all(&sum, &sorted[index + 1..index + k])) {
+                if smallest >= *target && get_distance(&smallest, target)? >= best.distance {
+                    break;
+                }
+            }
+            if let (Some(best), Some(largest)) = (best.as_ref(), add_all(&sum, &sorted[sorted.len() + 1 - k..])) {
+                if largest < *target && get_distance(&largest, target)? >= best.distance {
+                    continue;
+                }
+            }
+
+            prefix.push(value.clone());
+            find_sorted_closest(&sorted[index + 1..], k - 1, target, prefix, &sum, best)?;
+            prefix.pop();
+        },
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day1::Expense;
+    use crate::day1::analysis::{Closest, ExpenseReport};
+    use crate::utils::XorShift;
+
+    fn expenses(values: &[i64]) -> Vec<Expense> {
+        values.iter().map(|value| Expense(*value)).collect()
+    }
+
+    fn report(values: &[i64]) -> ExpenseReport {
+        ExpenseReport::new(expenses(values))
+    }
+
+    #[test]
+    fn it_counts_k_subsets() {
+        let example = report(&[1721, 979, 366, 299, 675, 1456]);
+        assert_eq!(example.count_k_subsets(2, &Expense(2020)).unwrap(), 1);
+        assert_eq!(example.count_k_subsets(3, &Expense(2020)).unwrap(), 1);
+        assert_eq!(example.count_k_subsets(2, &Expense(2021)).unwrap(), 0);
+        assert_eq!(example.count_k_subsets(0, &Expense(0)).unwrap(), 1);
+
+        let duplicates = report(&[1, 2, 2, 3, 3]);
+        assert_eq!(duplicates.count_k_subsets(2, &Expense(5)).unwrap(), 4);
+        assert_eq!(duplicates.find_all(2, &Expense(5)).unwrap(), vec![expenses(&[2, 3])]);
+        assert_eq!(duplicates.count_k_subsets(3, &Expense(7)).unwrap(), 3);
+
+        assert_eq!(report(&[-3, 3, 0, 5, -5]).count_k_subsets(2, &Expense(0)).unwrap(), 2);
+    }
+
+    #[test]
+    fn it_finds_the_closest_sum() {
+        let example = report(&[1721, 979, 366, 299, 675, 1456]);
+        let exact = example.find_closest(3, &Expense(2020)).unwrap().unwrap();
+        assert_eq!(exact, Closest { expenses: expenses(&[366, 675, 979]), sum: Expense(2020), distance: Expense(0) });
+
+        let closest = example.find_closest(2, &Expense(100)).unwrap().unwrap();
+        assert_eq!((closest.expenses, closest.distance), (expenses(&[299, 366]), Expense(565)));
+        assert_eq!(example.find_closest(4, &Expense(10000)).unwrap().unwrap().sum, Expense(4831));
+        assert_eq!(example.find_closest(1, &Expense(1000)).unwrap().unwrap().expenses, expenses(&[979]));
+        assert_eq!(example.find_closest(7, &Expense(2020)).unwrap(), None);
+
+        assert_eq!(report(&[-10, 4, 7]).find_closest(2, &Expense(0)).unwrap().unwrap().sum, Expense(-3));
+    }
+
+    /// Distance of the closest sum of `k` values, trying every combination
+    fn get_closest_distance(values: &[i64], k: usize, target: i64) -> Option<i64> {
+        match (k, values.split_first()) {
+            (0, _) => Some(target.abs()),
+            (_, None) => None,
+            (_, Some((first, rest))) => [get_closest_distance(rest, k - 1, target - first), get_closest_distance(rest, k, target)]
+                .iter().flatten().min().copied(),
+        }
+    }
+
+    #[test]
+    fn it_prunes_searches() {
+        let mut rng = XorShift::new(20);
+        for _ in 0..50 {
+            let values: Vec<i64> = (0..12).map(|_| rng.next_below(200) as i64 - 50).collect();
+            let target = rng.next_below(600) as i64 - 100;
+            let mut distinct = values.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            for k in 3..=5 {
+                let closest = report(&values).find_closest(k, &Expense(target)).unwrap().unwrap();
+                assert_eq!(Some(closest.distance.0), get_closest_distance(&values, k, target), "{:?} {} {}", values, k, target);
+
+                let distinct = report(&distinct);
+                assert_eq!(distinct.find_all(k, &Expense(target)).unwrap().len() as u64,
+                           distinct.count_k_subsets(k, &Expense(target)).unwrap());
+            }
+        }
+
+        assert_eq!(report(&[1, 2]).count_k_subsets(3, &Expense(3)).unwrap(), 0);
+    }
+
+    #[test]
+    fn it_finds_subsets_of_any_size() {
+        let example = report(&[1721, 979, 366, 299, 675, 1456]);
+        for target in &[2020, 1340, 1721, 5496] {
+            let subset = example.find_subset(&Expense(*target)).unwrap().unwrap();
+            assert_eq!(subset.iter().map(|expense| expense.0).sum::<i64>(), *target);
+        }
+        assert_eq!(example.find_subset(&Expense(0)).unwrap(), Some(vec![]));
+        assert_eq!(example.find_subset(&Expense(1)).unwrap(), None);
+
+        assert_eq!(report(&[-5, 3, 8]).find_subset(&Expense(6)).unwrap(), Some(expenses(&[-5, 3, 8])));
+        assert_eq!(ExpenseReport::new(vec![Expense(100i8), Expense(100)]).find_subset(&Expense(127)).unwrap(), None);
+        let small = ExpenseReport::new(vec![Expense(100i8), Expense(100), Expense(27)]);
+        assert_eq!(small.find_subset(&Expense(127)).unwrap(), Some(vec![Expense(27), Expense(100)]));
+        assert_eq!(small.count_k_subsets(2, &Expense(127)).unwrap(), 2);
+        assert!(ExpenseReport::new(vec![Expense(-100i8), Expense(100), Expense(100)]).find_subset(&Expense(127)).is_err());
+    }
+}
@@ -1,143 +1,366 @@
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 use crate::utils::GenericError;
 use std::str::FromStr;
-use std::ops::{Add, Mul};
-use std::fmt::{Display, Formatter};
-
-pub fn find_complements2(input: &Vec<Expense>, goal: Expense) -> Option<(Expense, Expense)> {
-    for value1 in input {
-        for value2 in input {
-            if value1 + value2 == goal {
-                return Some((value1.clone(), value2.clone()));
-            }
-        }
-    }
+use std::ops::{Add, Sub, Mul};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+
+pub mod analysis;
+
+/// How many solutions a search looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Search {
+    First,
+    /// Every solution with a distinct set of values
+    All,
+}
+
+/// Solutions of a k-sum search, each one sorted in ascending order
+pub type Solutions<T> = Vec<Vec<Expense<T>>>;
+
+/// Find sets of `k` expenses summing to `goal`, each expense of the input being used at most once.
+///
+/// Solutions are sorted in ascending order, and stop at the first one unless searching them all.
+/// Sums out of the range of the expense type are passed over, as they cannot match `goal`.
+pub fn find_k_sum<T: Amount>(input: &[Expense<T>], k: usize, goal: &Expense<T>, search: Search) -> Result<Solutions<T>, GenericError> {
+    let mut sorted = input.to_vec();
+    sorted.sort();
 
-    return None;
+    let mut solutions = vec![];
+    find_sorted_k_sum(&sorted, k, goal, &mut vec![], &mut solutions, search)?;
+
+    Ok(solutions)
 }
 
-pub fn find_complements3(input: &Vec<Expense>, goal: Expense) -> Option<(Expense, Expense, Expense)> {
-    for value1 in input {
-        for value2 in input {
-            for value3 in input {
-                if value1 + value2 + value3 == goal {
-                    return Some((value1.clone(), value2.clone(), value3.clone()));
+fn find_sorted_k_sum<T: Amount>(sorted: &[Expense<T>], k: usize, goal: &Expense<T>, prefix: &mut Vec<Expense<T>>,
+                                solutions: &mut Solutions<T>, search: Search) -> Result<(), GenericError> {
+    let is_done = |solutions: &Solutions<T>| search == Search::First && !solutions.is_empty();
+
+    match k {
+        0 => if goal.0 == T::default() {
+            solutions.push(prefix.clone());
+        },
+        1 => if sorted.binary_search(goal).is_ok() {
+            solutions.push([prefix.as_slice(), std::slice::from_ref(goal)].concat());
+        },
+        2 => {
+            let (mut low, mut high) = (0, sorted.len().saturating_sub(1));
+            while low < high && !is_done(solutions) {
+                // A sum out of range is beyond the goal, above it when the largest expense is positive
+                let (is_below, is_above) = match &sorted[low] + &sorted[high] {
+                    Ok(sum) => (sum < *goal, sum > *goal),
+                    Err(GenericError::Overflow(_)) => {
+                        let is_above = sorted[high].0 > T::default();
+                        (!is_above, is_above)
+                    }
+                    Err(err) => return Err(err),
+                };
+                if is_below {
+                    low += 1;
+                } else if is_above {
+                    high -= 1;
+                } else {
+                    solutions.push([prefix.as_slice(), &[sorted[low].clone(), sorted[high].clone()]].concat());
+                    let (low_value, high_value) = (sorted[low].clone(), sorted[high].clone());
+                    while low < high && sorted[low] == low_value {
+                        low += 1;
+                    }
+                    while low < high && sorted[high] == high_value {
+                        high -= 1;
+                    }
                 }
             }
         }
+        _ => for (index, value) in sorted.iter().enumerate() {
+            // Expenses after a non-negative one can only make the sum grow
+            if (value > goal && value.0 >= T::default()) || sorted.len() - index < k || is_done(solutions) {
+                break;
+            }
+            if index > 0 && sorted[index - 1] == *value {
+                continue;
+            }
+            // Sums of the branch lie between these two, and both only grow with the index
+            if add_all(value, &sorted[index + 1..index + k]).is_some_and(|smallest| smallest > *goal) {
+                break;
+            }
+            if add_all(value, &sorted[sorted.len() + 1 - k..]).is_some_and(|largest| largest < *goal) {
+                continue;
+            }
+
+            // Other expenses cannot sum to a rest out of range without overflowing themselves
+            let rest = match goal - value {
+                Ok(rest) => rest,
+                Err(GenericError::Overflow(_)) => continue,
+                Err(err) => return Err(err),
+            };
+            prefix.push(value.clone());
+            find_sorted_k_sum(&sorted[index + 1..], k - 1, &rest, prefix, solutions, search)?;
+            prefix.pop();
+        },
     }
 
-    return None;
+    Ok(())
+}
+
+/// `values` added to `sum`, none when it overflows
+pub fn add_all<T: Amount>(sum: &Expense<T>, values: &[Expense<T>]) -> Option<Expense<T>> {
+    values.iter().try_fold(sum.clone(), |sum, value| (&sum + value).ok())
+}
+
+/// Describe the expenses of a solution, such as "366, 675 and 979"
+pub fn describe<T: Amount>(expenses: &[Expense<T>]) -> String {
+    match expenses.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, others)) => {
+            let others: Vec<String> = others.iter().map(|expense| expense.to_string()).collect();
+            format!("{} and {}", others.join(", "), last)
+        }
+        None => String::new(),
+    }
 }
 
 #[derive(Default)]
 pub struct Day1 {}
 
-#[derive(Clone, Debug)]
-pub struct Expense(usize);
+/// Integer an expense is made of, its default value being zero
+pub trait Amount: Clone + Debug + Default + Display + Hash + Ord {
+    fn parse(s: &str) -> Result<Self, GenericError>;
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+}
 
-impl DailyChallenge for Day1 {
-    type Data = Expense;
-    type Wrapper = Vec<Expense>;
+macro_rules! impl_amount {
+    ($($t:ty),*) => {
+        $(impl Amount for $t {
+            fn parse(s: &str) -> Result<Self, GenericError> {
+                Ok(s.parse()?)
+            }
 
-    fn get_day_num(&self) -> usize { 1 }
+            fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *rhs)
+            }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        match find_complements2(data, Expense(2020)) {
-            Some((val1, val2)) => Ok(format!("found {} and {}, their product is {}", &val1, &val2, &val1 * &val2)),
-            None => Err(GenericError::new("Could not find 2 values that match 2020...".to_string()))
-        }
+            fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *rhs)
+            }
+
+            fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *rhs)
+            }
+        })*
+    };
+}
+
+impl_amount!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Arbitrary precision, which never overflows
+#[cfg(feature = "bigint")]
+impl Amount for num_bigint::BigInt {
+    fn parse(s: &str) -> Result<Self, GenericError> {
+        s.parse().map_err(|err: num_bigint::ParseBigIntError| GenericError::Parse {
+            message: err.to_string(),
+            location: None,
+            source: Some(Box::new(err)),
+        })
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        match find_complements3(data, Expense(2020)) {
-            Some((val1, val2, val3)) => Ok(format!("found {}, {} and {}, their product is {}", &val1, &val2, &val3, &val1 * &val2 * &val3)),
-            None => Err(GenericError::new("Could not find 3 values that match 2020...".to_string()))
-        }
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
     }
-}
 
-impl FromStr for Expense {
-    type Err = GenericError;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs)
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let num = s.parse()?;
-        Ok(Expense(num))
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
     }
 }
 
-impl Add for Expense {
-    type Output = Self;
+/// An expense of the report, signed 64 bits unless told otherwise
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Expense<T = i64>(T);
 
-    fn add(self, rhs: Self) -> Self::Output {
-        Expense(self.0 + rhs.0)
+impl<T> From<T> for Expense<T> {
+    fn from(value: T) -> Self {
+        Expense(value)
     }
 }
 
-impl<'a, 'b> Add<&'b Expense> for &'a Expense {
-    type Output = Expense;
+impl DailyChallenge for Day1 {
+    type Data = Expense;
+    type Wrapper = Vec<Expense>;
+
+    fn get_day_num(&self) -> usize { 1 }
 
-    fn add(self, rhs: &'b Expense) -> Self::Output {
-        Expense(self.0 + rhs.0)
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("514579"),
+                part_2: Some("241861950"),
+            },
+        ]
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        solve_k_sum(data, 2)
+    }
+
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        solve_k_sum(data, 3)
     }
 }
 
-impl<'a> Add<Expense> for &'a Expense {
-    type Output = Expense;
+/// Sum the puzzle looks for
+pub const TARGET: i64 = 2020;
 
-    fn add(self, rhs: Expense) -> Self::Output {
-        Expense(self.0 + rhs.0)
+/// Product of the first `k` expenses that sum to the target
+fn solve_k_sum(data: &[Expense], k: usize) -> Result<Answer, GenericError> {
+    match find_k_sum(data, k, &Expense(TARGET), Search::First)?.into_iter().next() {
+        Some(expenses) => {
+            let product = expenses.iter().skip(1).try_fold(expenses[0].clone(), |product, expense| product * expense)?;
+            Ok(Answer::new(product).with_explanation(format!("product of {}", describe(&expenses))))
+        }
+        None => Err(GenericError::NoSolution(format!("Could not find {} values that match {}...", k, TARGET)))
     }
 }
 
-impl<'a> Add<&'a Expense> for Expense {
-    type Output = Expense;
+impl<T: Amount> FromStr for Expense<T> {
+    type Err = GenericError;
 
-    fn add(self, rhs: &'a Expense) -> Self::Output {
-        Expense(self.0 + rhs.0)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Expense(T::parse(s)?))
     }
 }
 
-impl Display for Expense {
+impl<T: Amount> Display for Expense<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-impl Mul for Expense {
-    type Output = Self;
+/// Implement an operator for every combination of owned and borrowed expenses, failing on overflow
+macro_rules! impl_checked_operator {
+    ($trait:ident, $method:ident, $checked:ident, $symbol:literal) => {
+        impl<T: Amount> $trait<&Expense<T>> for &Expense<T> {
+            type Output = Result<Expense<T>, GenericError>;
 
-    fn mul(self, rhs: Self) -> Self::Output {
-        Expense(self.0 * rhs.0)
-    }
+            fn $method(self, rhs: &Expense<T>) -> Self::Output {
+                self.0.$checked(&rhs.0)
+                    .map(Expense)
+                    .ok_or_else(|| GenericError::Overflow(format!(concat!("{} ", $symbol, " {} overflows"), self, rhs)))
+            }
+        }
+
+        impl<T: Amount> $trait<Expense<T>> for &Expense<T> {
+            type Output = Result<Expense<T>, GenericError>;
+
+            fn $method(self, rhs: Expense<T>) -> Self::Output {
+                self.$method(&rhs)
+            }
+        }
+
+        impl<T: Amount> $trait<&Expense<T>> for Expense<T> {
+            type Output = Result<Expense<T>, GenericError>;
+
+            fn $method(self, rhs: &Expense<T>) -> Self::Output {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl<T: Amount> $trait for Expense<T> {
+            type Output = Result<Expense<T>, GenericError>;
+
+            fn $method(self, rhs: Expense<T>) -> Self::Output {
+                (&self).$method(&rhs)
+            }
+        }
+    };
 }
 
-impl<'a, 'b> Mul<&'b Expense> for &'a Expense {
-    type Output = Expense;
+impl_checked_operator!(Add, add, checked_add, "+");
+impl_checked_operator!(Sub, sub, checked_sub, "-");
+impl_checked_operator!(Mul, mul, checked_mul, "*");
+
+#[cfg(test)]
+mod tests {
+    use crate::day1::{find_k_sum, Expense, Search, Solutions};
+    use crate::utils::GenericError;
 
-    fn mul(self, rhs: &'b Expense) -> Self::Output {
-        Expense(self.0 * rhs.0)
+    fn expenses(values: &[i64]) -> Vec<Expense> {
+        values.iter().map(|value| Expense(*value)).collect()
     }
-}
 
-impl<'b> Mul<&'b Expense> for Expense {
-    type Output = Expense;
+    fn search(values: &[i64], k: usize, goal: i64, search: Search) -> Solutions<i64> {
+        find_k_sum(&expenses(values), k, &Expense(goal), search).unwrap()
+    }
 
-    fn mul(self, rhs: &'b Expense) -> Self::Output {
-        Expense(self.0 * rhs.0)
+    #[test]
+    fn it_never_reuses_an_expense() {
+        assert!(search(&[1010, 5], 2, 2020, Search::All).is_empty());
+        assert_eq!(search(&[1010, 5, 1010], 2, 2020, Search::All), vec![expenses(&[1010, 1010])]);
+        assert!(search(&[700, 5, 620], 3, 2020, Search::All).is_empty());
     }
-}
 
-impl<'a> Mul<Expense> for &'a Expense {
-    type Output = Expense;
+    #[test]
+    fn it_finds_k_sums() {
+        let input = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(search(&input, 2, 2020, Search::First), vec![expenses(&[299, 1721])]);
+        assert_eq!(search(&input, 3, 2020, Search::First), vec![expenses(&[366, 675, 979])]);
+        assert_eq!(search(&input, 1, 979, Search::First), vec![expenses(&[979])]);
+        assert_eq!(search(&input, 0, 0, Search::First), vec![vec![]]);
+        assert!(search(&input, 7, 2020, Search::All).is_empty());
+    }
 
-    fn mul(self, rhs: Expense) -> Self::Output {
-        Expense(self.0 * rhs.0)
+    #[test]
+    fn it_finds_all_distinct_solutions() {
+        let input = [1, 2, 2, 3, 4, 5, 5];
+        assert_eq!(search(&input, 2, 7, Search::All), vec![expenses(&[2, 5]), expenses(&[3, 4])]);
+        assert_eq!(search(&input, 3, 9, Search::All),
+                   vec![expenses(&[1, 3, 5]), expenses(&[2, 2, 5]), expenses(&[2, 3, 4])]);
+        assert_eq!(search(&input, 4, 10, Search::All).len(), 2);
     }
-}
 
-impl PartialEq for Expense {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
+    #[test]
+    fn it_handles_negative_expenses() {
+        assert_eq!("-15".parse::<Expense>().unwrap(), Expense(-15));
+        assert_eq!(search(&[-5, 2025, 10, 2010], 2, 2020, Search::All), vec![expenses(&[-5, 2025]), expenses(&[10, 2010])]);
+        assert_eq!(search(&[3000, -1000, 20, 1990, 10], 3, 2020, Search::All),
+                   vec![expenses(&[-1000, 20, 3000]), expenses(&[10, 20, 1990])]);
+        assert!("-15".parse::<Expense<u32>>().is_err());
+    }
+
+    #[test]
+    fn it_reports_overflows() {
+        assert_eq!((Expense(100i8) + Expense(27)).unwrap(), Expense(127));
+        assert!(matches!(Expense(100i8) + Expense(28), Err(GenericError::Overflow(_))));
+        assert!(matches!(&Expense(u64::MAX) * &Expense(2), Err(GenericError::Overflow(_))));
+        assert!(matches!(Expense(0u8) - &Expense(1), Err(GenericError::Overflow(_))));
+        assert!(find_k_sum(&[Expense(100i8), Expense(120)], 2, &Expense(5), Search::First).unwrap().is_empty());
+        assert_eq!(find_k_sum(&[Expense(100i8), Expense(120), Expense(5), Expense(0)], 2, &Expense(5), Search::All).unwrap(),
+                   vec![vec![Expense(0), Expense(5)]]);
+        assert_eq!(find_k_sum(&[Expense(-100i8), Expense(-120), Expense(-5), Expense(0)], 2, &Expense(-5), Search::All).unwrap(),
+                   vec![vec![Expense(-5), Expense(0)]]);
+        let expenses = [Expense(100i8), Expense(120), Expense(-115), Expense(-128), Expense(5), Expense(0)];
+        assert_eq!(find_k_sum(&expenses, 3, &Expense(5), Search::All).unwrap(), vec![vec![Expense(-115), Expense(0), Expense(120)]]);
+
+        let large = Expense(i64::MAX as i128);
+        assert_eq!((&large * &large).unwrap().to_string(), "85070591730234615847396907784232501249");
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn it_never_overflows_big_integers() {
+        let large: Expense<num_bigint::BigInt> = "340282366920938463463374607431768211455".parse().unwrap();
+        assert_eq!((&large * &large).unwrap().to_string(),
+                   "115792089237316195423570985008687907852589419931798687112530834793049593217025");
+
+        let input: Vec<Expense<num_bigint::BigInt>> = ["-7", "1721", "979", "366", "299", "675", "1456"].iter()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let goal = "2020".parse().unwrap();
+        assert_eq!(find_k_sum(&input, 2, &goal, Search::All).unwrap().len(), 1);
     }
 }
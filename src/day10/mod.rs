@@ -1,4 +1,4 @@
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 use crate::utils::GenericError;
 
 #[derive(Default)]
@@ -10,7 +10,22 @@ impl DailyChallenge for Day10 {
 
     fn get_day_num(&self) -> usize { 10 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("35"),
+                part_2: Some("8"),
+            },
+            Example {
+                input: include_str!("example2.txt"),
+                part_1: Some("220"),
+                part_2: Some("19208"),
+            },
+        ]
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let mut sorted_data = data.clone();
         sorted_data.sort();
         sorted_data.push(sorted_data.last().unwrap() + 3);
@@ -26,10 +41,10 @@ impl DailyChallenge for Day10 {
                     _ => (current_val, nb1, nb2, nb3, Some(format!("{} cannot be chained", i))),
                 }
             });
-        Ok(format!("result is {}", results.1 * results.3))
+        Ok(Answer::new(results.1 * results.3).with_explanation(format!("{} 1-jolt differences times {} 3-jolt differences", results.1, results.3)))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let mut sorted_data = data.clone();
         sorted_data.sort();
         sorted_data.insert(0, 0);
@@ -54,7 +69,27 @@ impl DailyChallenge for Day10 {
                 5 => 7, // 2^3 - 1
                 _ => 0
             }
-        }).fold(1, |acc: usize, cur| acc * cur);
-        Ok(format!("result is {}", result))
+        }).product::<usize>();
+        Ok(Answer::new(result).with_explanation("distinct adapter arrangements".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day10::Day10;
+    use crate::daily_challenge::{DailyChallenge, Input};
+    use crate::utils::LoadMode;
+
+    #[test]
+    fn it_loads_in_memory_text() {
+        let input = Input::Text("16\n10\nx\n15\n".to_string());
+        let (adapters, warnings) = Day10.load_data(&input, LoadMode::Lenient).unwrap();
+        assert_eq!(adapters, vec![16, 10, 15]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("text:3: record 3 'x'"), "{}", warnings[0]);
+
+        assert!(Day10.parse_input("16\n10\nx\n15\n").is_err());
+        assert_eq!(Day10.parse_input("16\n10\n").unwrap(), vec![16, 10]);
+        assert_eq!(input.clone().buffered().unwrap(), input);
     }
 }
@@ -0,0 +1,179 @@
+use std::mem;
+use crate::automaton::Simulation;
+use crate::day11::{Grid, Position};
+
+const WORD_BITS: usize = 64;
+
+/// Bit-sliced counter of up to 8 neighbours for 64 positions at once, each plane holding one bit of the counts
+#[derive(Default)]
+struct Counter {
+    ones: u64,
+    twos: u64,
+    fours: u64,
+    eights: u64,
+}
+
+impl Counter {
+    fn add(&mut self, neighbours: u64) {
+        let carry = self.ones & neighbours;
+        self.ones ^= neighbours;
+        let carry_twos = self.twos & carry;
+        self.twos ^= carry;
+        let carry_fours = self.fours & carry_twos;
+        self.fours ^= carry_twos;
+        self.eights |= carry_fours;
+    }
+
+    fn get_any(&self) -> u64 {
+        self.ones | self.twos | self.fours | self.eights
+    }
+
+    fn get_at_least_four(&self) -> u64 {
+        self.fours | self.eights
+    }
+}
+
+/// Day 11 grid packed as bits, one mask of seats and one of occupied seats, following the adjacent rule only
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    seats: Vec<u64>,
+    occupied: Vec<u64>,
+    buffer: Vec<u64>,
+    /// Row of empty seats beyond the top and bottom edges
+    empty_row: Vec<u64>,
+}
+
+impl From<&Grid> for BitGrid {
+    fn from(grid: &Grid) -> Self {
+        let (width, height) = (grid.get_width(), grid.get_height());
+        let words_per_row = width.div_ceil(WORD_BITS);
+        let mut seats = vec![0; words_per_row * height];
+        let mut occupied = vec![0; words_per_row * height];
+
+        for (index, position) in grid.get_cells().iter().enumerate() {
+            let (x, y) = (index % width, index / width);
+            let word = y * words_per_row + x / WORD_BITS;
+            let bit = 1 << (x % WORD_BITS);
+            match position {
+                Position::Floor => {}
+                Position::FreeSeat => seats[word] |= bit,
+                Position::OccupiedSeat => {
+                    seats[word] |= bit;
+                    occupied[word] |= bit;
+                }
+            }
+        }
+
+        BitGrid { width, height, words_per_row, seats, buffer: occupied.clone(), occupied, empty_row: vec![0; words_per_row] }
+    }
+}
+
+impl BitGrid {
+    pub fn count_occupied(&self) -> usize {
+        self.occupied.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Back to the enum representation
+    #[cfg(test)]
+    pub fn to_grid(&self) -> Grid {
+        let cells = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let word = y * self.words_per_row + x / WORD_BITS;
+                let bit = 1 << (x % WORD_BITS);
+                match (self.seats[word] & bit != 0, self.occupied[word] & bit != 0) {
+                    (false, _) => Position::Floor,
+                    (true, false) => Position::FreeSeat,
+                    (true, true) => Position::OccupiedSeat,
+                }
+            })
+            .collect();
+
+        crate::grid::Grid::new(self.width, cells).unwrap()
+    }
+}
+
+impl Simulation for BitGrid {
+    /// Occupied seats, the seats themselves never changing
+    type State = Vec<u64>;
+
+    fn step(&mut self) -> bool {
+        let words = self.words_per_row;
+        let mut next = mem::take(&mut self.buffer);
+
+        for y in 0..self.height {
+            let row = |y: usize| &self.occupied[y * words..(y + 1) * words];
+            let above = if y > 0 { row(y - 1) } else { &self.empty_row };
+            let current = row(y);
+            let below = if y + 1 < self.height { row(y + 1) } else { &self.empty_row };
+
+            for word in 0..words {
+                // Neighbours on the left of a position are the bits just below it, and conversely
+                let west = |row: &[u64]| (row[word] << 1) | if word > 0 { row[word - 1] >> (WORD_BITS - 1) } else { 0 };
+                let east = |row: &[u64]| (row[word] >> 1) | if word + 1 < words { row[word + 1] << (WORD_BITS - 1) } else { 0 };
+
+                let mut counter = Counter::default();
+                for row in &[above, below] {
+                    counter.add(west(row));
+                    counter.add(row[word]);
+                    counter.add(east(row));
+                }
+                counter.add(west(current));
+                counter.add(east(current));
+
+                let occupied = current[word];
+                let stays_occupied = occupied & !counter.get_at_least_four();
+                let gets_occupied = !occupied & !counter.get_any();
+                next[y * words + word] = self.seats[y * words + word] & (stays_occupied | gets_occupied);
+            }
+        }
+
+        let changed = next != self.occupied;
+        self.buffer = mem::replace(&mut self.occupied, next);
+
+        changed
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.occupied.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day11::{Grid, Neighbourhood};
+    use crate::day11::bitboard::BitGrid;
+    use crate::automaton::Simulation;
+    use crate::utils::XorShift;
+
+    #[test]
+    fn it_seats_the_example() {
+        let grid: Grid = include_str!("example.txt").trim_end().parse().unwrap();
+        let mut bit_grid = BitGrid::from(&grid);
+        assert_eq!(bit_grid.to_grid(), grid);
+
+        let steady_state = bit_grid.run_until_steady(100).unwrap();
+        assert!(steady_state.is_fixed_point());
+        assert_eq!(steady_state.start, 5);
+        assert_eq!(bit_grid.count_occupied(), 37);
+    }
+
+    #[test]
+    fn it_matches_the_enum_grid() {
+        let mut rng = XorShift::new(11);
+        for (width, height) in &[(1, 1), (7, 5), (63, 4), (64, 3), (65, 9), (130, 17), (200, 1)] {
+            let grid = Grid::random(*width, *height, &mut rng);
+            let mut automaton = grid.to_automaton(Neighbourhood::Adjacent);
+            let mut bit_grid = BitGrid::from(&grid);
+
+            for _ in 0..10 {
+                assert_eq!(bit_grid.step(), automaton.step());
+                assert_eq!(&bit_grid.to_grid(), automaton.get_grid(), "{}x{} grids differ", width, height);
+            }
+            assert_eq!(bit_grid.count_occupied(), automaton.get_grid().count_occupied());
+        }
+    }
+}
@@ -1,126 +1,197 @@
-use std::str::FromStr;
-use crate::utils::GenericError;
-use crate::daily_challenge::DailyChallenge;
-use std::cmp::{min, max};
+use crate::utils::{GenericError, XorShift};
+use crate::daily_challenge::{DailyChallenge, Answer, Example, Part};
+use crate::automaton::{self, Automaton, ParallelAutomaton, Rule, Simulation, SteadyState};
+use crate::frames::Frame;
+use crate::grid::{self, Cell};
+use bitboard::BitGrid;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+mod bitboard;
+
+/// Rounds after which the seating is considered as never stabilizing
+const MAX_ROUNDS: usize = 1000;
+
+/// Positions from which rounds are computed on every core, when there are several
+const PARALLEL_POSITIONS: usize = 250_000;
+
+/// Colours of the floor, free seats and occupied seats in image frames
+pub const PALETTE: [[u8; 3]; 3] = [[40, 40, 40], [60, 180, 75], [230, 25, 75]];
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Position {
     FreeSeat,
     OccupiedSeat,
     Floor,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Default)]
-pub struct Row {
-    positions: Vec<Position>
+/// Which seats are considered when a seat mutates
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Neighbourhood {
+    /// The eight positions around the seat
+    Adjacent,
+    /// The first seat visible in each of the eight directions, floor excluded
+    LineOfSight,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Default)]
-pub struct Grid {
-    rows: Vec<Row>
-}
-
-impl Grid {
-    pub fn get_position(&self, x: usize, y: usize) -> Option<&Position> {
-        if y >= self.rows.len() {
-            return None;
+impl Neighbourhood {
+    /// Count of occupied neighbours from which an occupied seat gets free
+    pub fn get_tolerance(&self) -> usize {
+        match self {
+            Neighbourhood::Adjacent => 4,
+            Neighbourhood::LineOfSight => 5,
         }
+    }
 
-        let row = &self.rows[y];
-
-        if x >= row.positions.len() {
-            return None;
+    fn get_shape(&self) -> automaton::Neighbourhood<Position> {
+        match self {
+            Neighbourhood::Adjacent => automaton::Neighbourhood::Moore,
+            Neighbourhood::LineOfSight => automaton::Neighbourhood::LineOfSight(|position| *position == Position::Floor),
         }
+    }
+}
 
-        let position = &row.positions[x];
-
-        Some(position)
-    }
-
-    pub fn mutate(&self) -> Self {
-        let mut new_grid = Grid::default();
-        for (current_y, row) in (&self.rows).into_iter().enumerate() {
-            let mut new_row = Row::default();
-            for (current_x, position) in (&row.positions).into_iter().enumerate() {
-                let y_range = (max(1, current_y) - 1)..min(self.rows.len(), current_y + 2);
-                let x_range = (max(1, current_x) - 1)..min(row.positions.len(), current_x + 2);
-                let adjacent_positions = x_range.into_iter()
-                    .fold(vec![], |acc, x| {
-                        let xy_range = y_range.clone().into_iter()
-                            .map(|y| (x, y))
-                            .filter(|(i, j)| &current_x != i || &current_y != j)
-                            .collect::<Vec<(usize, usize)>>();
-                        acc.into_iter().chain(xy_range).collect::<Vec<(usize, usize)>>()
-                    })
-                    .into_iter()
-                    .map(|(i, j)| self.get_position(i, j))
-                    .filter(|pos| pos.is_some())
-                    .map(|pos| pos.unwrap())
-                    .collect::<Vec<&Position>>();
-
-                new_row.positions.push(position.mutate(adjacent_positions));
-            }
-
-            new_grid.rows.push(new_row);
+impl From<Part> for Neighbourhood {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::One => Neighbourhood::Adjacent,
+            Part::Two => Neighbourhood::LineOfSight,
         }
+    }
+}
 
+/// Seats get occupied when no neighbour is, and free when at least `tolerance` neighbours are
+#[derive(Debug, Clone, Copy)]
+pub struct SeatingRule {
+    tolerance: usize,
+}
 
-        new_grid
+impl Rule<Position> for SeatingRule {
+    fn apply(&self, cell: &Position, neighbours: &[&Position]) -> Position {
+        cell.mutate(neighbours, self.tolerance)
     }
+}
 
-    pub fn count_occupied(&self) -> usize {
-        (&self.rows).into_iter()
-            .map(|row| (&row.positions).into_iter().filter(|pos| **pos == Position::OccupiedSeat).count())
-            .sum()
+pub type SeatingAutomaton = Automaton<Position, SeatingRule>;
+
+/// Round from which the seating does not change anymore
+fn get_stable_round(steady_state: SteadyState) -> Result<usize, GenericError> {
+    if !steady_state.is_fixed_point() {
+        return Err(GenericError::NoSolution(format!("Seating oscillates every {} rounds from round {}",
+                                                    steady_state.period, steady_state.start)));
     }
+
+    Ok(steady_state.start)
 }
 
-impl FromStr for Position {
-    type Err = GenericError;
+pub type Row = grid::Row<Position>;
+pub type Grid = grid::Grid<Position>;
+
+impl Grid {
+    /// Grid of random positions, for benchmarks
+    pub fn random(width: usize, height: usize, rng: &mut XorShift) -> Self {
+        let cells = (0..width * height)
+            .map(|_| match rng.next_below(3) {
+                0 => Position::Floor,
+                1 => Position::FreeSeat,
+                _ => Position::OccupiedSeat,
+            })
+            .collect();
+
+        grid::Grid::new(width, cells).expect("Cells fill every row")
+    }
+
+    /// Build the automaton simulating this grid
+    pub fn to_automaton(&self, neighbourhood: Neighbourhood) -> SeatingAutomaton {
+        let rule = SeatingRule { tolerance: neighbourhood.get_tolerance() };
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "L" => Ok(Position::FreeSeat),
-            "#" => Ok(Position::OccupiedSeat),
-            "." => Ok(Position::Floor),
-            _ => GenericError::throw("Invalid input for position")
+        Automaton::new(self.clone(), neighbourhood.get_shape(), rule)
+    }
+
+    /// Mutate the grid until it does not change anymore, returns the stable grid and the round it appeared
+    pub fn stabilize(&self, neighbourhood: Neighbourhood) -> Result<(Self, usize), GenericError> {
+        let automaton = self.to_automaton(neighbourhood);
+        let threads = automaton::get_available_threads();
+        if self.get_cells().len() >= PARALLEL_POSITIONS && threads > 1 {
+            let mut automaton = ParallelAutomaton::new(automaton, threads);
+            let round = get_stable_round(automaton.run_until_steady(MAX_ROUNDS)?)?;
+            return Ok((automaton.get_grid().clone(), round));
         }
+
+        let mut automaton = automaton;
+        let round = get_stable_round(automaton.run_until_steady(MAX_ROUNDS)?)?;
+
+        Ok((automaton.get_grid().clone(), round))
     }
-}
 
-impl FromStr for Row {
-    type Err = GenericError;
+    /// Every round from this grid up to the stable one
+    pub fn get_generations(&self, neighbourhood: Neighbourhood) -> Result<Vec<Self>, GenericError> {
+        let mut automaton = self.to_automaton(neighbourhood);
+        let mut generations = vec![];
+        let steady_state = automaton.run_until_steady_with(MAX_ROUNDS, |automaton| generations.push(automaton.get_grid().clone()))?;
+        generations.truncate(get_stable_round(steady_state)? + 1);
+
+        Ok(generations)
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let positions: Vec<Position> = s.chars().into_iter().map(|c| c.to_string().parse()).collect::<Result<Vec<Position>, GenericError>>()?;
+    /// Image frame of the grid, colours being indexes in `PALETTE`
+    pub fn to_frame(&self) -> Frame {
+        Frame {
+            width: self.get_width(),
+            height: self.get_height(),
+            cells: self.get_cells().iter().map(Position::get_palette_index).collect(),
+        }
+    }
 
-        Ok(Row { positions })
+    pub fn count_occupied(&self) -> usize {
+        self.get_cells().iter()
+            .filter(|position| **position == Position::OccupiedSeat)
+            .count()
     }
 }
 
-impl From<Vec<Row>> for Grid {
-    fn from(rows: Vec<Row>) -> Self {
-        Grid { rows }
+impl Cell for Position {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'L' => Some(Position::FreeSeat),
+            '#' => Some(Position::OccupiedSeat),
+            '.' => Some(Position::Floor),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Position::FreeSeat => 'L',
+            Position::OccupiedSeat => '#',
+            Position::Floor => '.',
+        }
     }
 }
 
 impl Position {
-    fn mutate(&self, adjacent_positions: Vec<&Position>) -> Position {
+    fn get_palette_index(&self) -> u8 {
+        match self {
+            Position::Floor => 0,
+            Position::FreeSeat => 1,
+            Position::OccupiedSeat => 2,
+        }
+    }
+
+    fn mutate(&self, neighbours: &[&Position], tolerance: usize) -> Position {
         if let Position::Floor = self {
             return Position::Floor;
         }
 
-        let occupied_seat_count = adjacent_positions.into_iter()
-            .filter(|pos| **pos == Position::OccupiedSeat)
+        let occupied_seat_count = neighbours.iter()
+            .filter(|pos| ***pos == Position::OccupiedSeat)
             .count();
 
-        return if *self == Position::OccupiedSeat && occupied_seat_count >= 4 {
+        if *self == Position::OccupiedSeat && occupied_seat_count >= tolerance {
             Position::FreeSeat
         } else if *self == Position::FreeSeat && occupied_seat_count == 0 {
             Position::OccupiedSeat
         } else {
             self.clone()
-        };
+        }
     }
 }
 
@@ -133,25 +204,34 @@ impl DailyChallenge for Day11 {
 
     fn get_day_num(&self) -> usize { 11 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        let mut new_data = (data.clone(), data.mutate());
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("37"),
+                part_2: Some("26"),
+            },
+        ]
+    }
 
-        while new_data.0 != new_data.1 {
-            let mutated_data = new_data.1.mutate();
-            new_data = (new_data.1, mutated_data);
-        }
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        let mut bit_grid = BitGrid::from(data);
+        let generation = get_stable_round(bit_grid.run_until_steady(MAX_ROUNDS)?)?;
 
-        Ok(format!("occupied seat count is {}", new_data.1.count_occupied()))
+        Ok(Answer::new(bit_grid.count_occupied()).with_explanation(format!("occupied seats once stable, after {} rounds", generation)))
     }
 
-    fn solve_part_2(&self, _data: &Self::Wrapper) -> Result<String, GenericError> {
-        Ok(format!("ok"))
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        let (stable_grid, generation) = data.stabilize(Neighbourhood::LineOfSight)?;
+
+        Ok(Answer::new(stable_grid.count_occupied()).with_explanation(format!("occupied seats once stable by line of sight, after {} rounds", generation)))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day11::{Grid, Row};
+    use crate::day11::{Grid, Row, Position, Neighbourhood};
+    use std::convert::TryFrom;
     use std::str::FromStr;
 
     #[test]
@@ -171,20 +251,61 @@ mod tests {
 
 
         let rows_result = input.into_iter()
-            .map(|s| Row::from_str(s))
+            .map(Row::from_str)
             .collect::<Result<Vec<Row>, _>>();
 
         assert!(rows_result.is_ok());
-        let grid: Grid = rows_result.unwrap().into();
+        let grid = Grid::try_from(rows_result.unwrap()).unwrap();
 
-        let mut new_data = (grid.clone(), grid.mutate());
+        let (stable_grid, generation) = grid.stabilize(Neighbourhood::Adjacent).unwrap();
+        assert_eq!(stable_grid.count_occupied(), 37);
+        assert_eq!(generation, 5);
 
-        while new_data.0 != new_data.1 {
-            let mutated_data = new_data.1.mutate();
-            new_data = (new_data.1, mutated_data);
-        }
+        let (stable_grid, generation) = grid.stabilize(Neighbourhood::LineOfSight).unwrap();
+        assert_eq!(stable_grid.count_occupied(), 26);
+        assert_eq!(generation, 6);
+    }
+
+    fn count_visible_occupied(input: &str, x: usize, y: usize) -> usize {
+        let grid: Grid = input.parse().unwrap();
+
+        let automaton = grid.to_automaton(Neighbourhood::LineOfSight);
+        let mut neighbours = vec![];
+        automaton.collect_neighbours(x, y, &mut neighbours);
+
+        neighbours.into_iter()
+            .filter(|position| **position == Position::OccupiedSeat)
+            .count()
+    }
+
+    #[test]
+    fn it_displays_grids() {
+        let input = include_str!("example.txt").trim_end();
+        let grid: Grid = input.parse().unwrap();
+        assert_eq!(grid.to_string(), input);
+
+        let generations = grid.get_generations(Neighbourhood::Adjacent).unwrap();
+        assert_eq!(generations.len(), 6);
+        assert_eq!(generations[1].to_string().lines().next(), Some("#.##.##.##"));
+
+        let reparsed = generations[2].to_string().lines().map(Row::from_str).collect::<Result<Vec<Row>, _>>().unwrap();
+        assert_eq!(Grid::try_from(reparsed).unwrap(), generations[2]);
+
+        let frame = generations[1].to_frame();
+        assert_eq!((frame.width, frame.height), (10, 10));
+        assert_eq!(&frame.cells[..3], &[2, 0, 2]);
+    }
+
+    #[test]
+    fn it_sees_first_seats() {
+        let input = ".......#.\n...#.....\n.#.......\n.........\n..#L....#\n....#....\n.........\n#........\n...#.....";
+        assert_eq!(count_visible_occupied(input, 3, 4), 8);
+
+        let input = ".............\n.L.L.#.#.#.#.\n.............";
+        assert_eq!(count_visible_occupied(input, 1, 1), 0);
 
-        assert_eq!(new_data.1.count_occupied(), 37);
+        let input = ".##.##.\n#.#.#.#\n##...##\n...L...\n##...##\n#.#.#.#\n.##.##.";
+        assert_eq!(count_visible_occupied(input, 3, 3), 0);
     }
 }
 
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use serde::Serialize;
+use crate::day2::PasswordRequirement;
+use crate::utils::Records;
+
+/// Failing letters listed in a report
+const TOP_LETTERS: usize = 5;
+
+/// Why an entry fails one of the puzzle policies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum Violation {
+    /// Part 1: the letter count is outside the allowed range
+    Count { count: usize, min: usize, max: usize },
+    /// Part 2: the letter is at both positions, or at neither
+    Positions { positions: [usize; 2], matching: Vec<usize> },
+    /// Part 2: a position does not fall within the password
+    OutOfRange { position: usize, length: usize },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Count { count, min, max } => write!(f, "count {} not in {}-{}", count, min, max),
+            Violation::Positions { positions: [first, second], matching } if matching.is_empty() =>
+                write!(f, "neither position {} nor {} matches", first, second),
+            Violation::Positions { positions: [first, second], .. } => write!(f, "both positions {} and {} match", first, second),
+            Violation::OutOfRange { position, length } => write!(f, "position {} beyond {} letters", position, length),
+        }
+    }
+}
+
+/// An entry failing at least one policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryAudit {
+    /// 1-based line of the entry in the input
+    pub line: usize,
+    pub letter: char,
+    pub password: String,
+    pub violations: Vec<Violation>,
+}
+
+impl PasswordRequirement {
+    /// Every policy the entry fails, see `is_valid` and `is_valid_part2`
+    pub fn get_violations(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        if !self.is_valid() {
+            violations.push(Violation::Count { count: self.count_letter(self.letter), min: self.min, max: self.max });
+        }
+
+        let positions = [self.min, self.max];
+        match positions.iter().find(|position| self.get_letter(**position).is_none()) {
+            Some(position) => violations.push(Violation::OutOfRange { position: *position, length: self.password.chars().count() }),
+            None => {
+                let matching: Vec<usize> = positions.iter()
+                    .copied()
+                    .filter(|position| self.get_letter(*position) == Some(self.letter))
+                    .collect();
+                if matching.len() != 1 {
+                    violations.push(Violation::Positions { positions, matching });
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// Entries failing the puzzle policies, with statistics over the whole input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditReport {
+    pub entries: usize,
+    pub failures: Vec<EntryAudit>,
+    /// Letters required by the failing entries, the most common first
+    pub failing_letters: Vec<(char, usize)>,
+    /// Number of passwords of each length, in letters
+    pub lengths: BTreeMap<usize, usize>,
+}
+
+/// Audit every entry of an input, located by its line
+pub fn audit(records: Records<PasswordRequirement>) -> AuditReport {
+    let entries = records.len();
+    let mut failures = vec![];
+    let mut letter_counts: HashMap<char, usize> = HashMap::new();
+    let mut lengths = BTreeMap::new();
+
+    for (requirement, position) in records {
+        *lengths.entry(requirement.password.chars().count()).or_insert(0) += 1;
+
+        let violations = requirement.get_violations();
+        if !violations.is_empty() {
+            *letter_counts.entry(requirement.letter).or_insert(0) += 1;
+            failures.push(EntryAudit { line: position.line, letter: requirement.letter, password: requirement.password, violations });
+        }
+    }
+
+    let mut failing_letters: Vec<(char, usize)> = letter_counts.into_iter().collect();
+    failing_letters.sort_by(|(letter, count), (other_letter, other_count)| other_count.cmp(count).then(letter.cmp(other_letter)));
+    failing_letters.truncate(TOP_LETTERS);
+
+    AuditReport { entries, failures, failing_letters, lengths }
+}
+
+impl Display for AuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self.failures.iter().map(|failure| failure.password.chars().count()).max().unwrap_or(0).max(8);
+        writeln!(f, "{:>5}  letter  {:<width$}  violations", "line", "password", width = width)?;
+        for failure in &self.failures {
+            let violations: Vec<String> = failure.violations.iter().map(|violation| violation.to_string()).collect();
+            writeln!(f, "{:>5}  {:<6}  {:<width$}  {}", failure.line, failure.letter, failure.password, violations.join(", "), width = width)?;
+        }
+
+        let describe = |pairs: Vec<String>| if pairs.is_empty() { "none".to_string() } else { pairs.join(", ") };
+        writeln!(f)?;
+        writeln!(f, "{} entries, {} failing at least one policy", self.entries, self.failures.len())?;
+        writeln!(f, "Most common failing letters : {}",
+                 describe(self.failing_letters.iter().map(|(letter, count)| format!("{} ({})", letter, count)).collect()))?;
+        write!(f, "Password lengths : {}",
+               describe(self.lengths.iter().map(|(length, count)| format!("{} ({})", length, count)).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day2::{Day2, PasswordRequirement};
+    use crate::day2::audit::{audit, Violation};
+    use crate::daily_challenge::{DailyChallenge, Input};
+    use crate::utils::LoadMode;
+
+    fn violations(line: &str) -> Vec<Violation> {
+        line.parse::<PasswordRequirement>().unwrap().get_violations()
+    }
+
+    #[test]
+    fn it_explains_violations() {
+        assert!(violations("1-3 a: abcde").is_empty());
+        assert_eq!(violations("1-3 b: cdefg"), vec![
+            Violation::Count { count: 0, min: 1, max: 3 },
+            Violation::Positions { positions: [1, 3], matching: vec![] },
+        ]);
+        assert_eq!(violations("2-9 c: ccccccccc"), vec![Violation::Positions { positions: [2, 9], matching: vec![2, 9] }]);
+        assert_eq!(violations("1-12 é: é"), vec![Violation::OutOfRange { position: 12, length: 1 }]);
+        assert_eq!(Violation::Count { count: 0, min: 1, max: 3 }.to_string(), "count 0 not in 1-3");
+    }
+
+    #[test]
+    fn it_audits_an_input() {
+        let day = Day2::default();
+        let input = Input::Text("1-3 a: abcde\n1-3 b: cdefg\nx\n2-9 c: ccccccccc\n1-3 b: bbbbe\n".to_string());
+        let (records, warnings) = day.load_records(&input, LoadMode::Lenient).unwrap();
+        assert_eq!(warnings.len(), 1);
+
+        let report = audit(records);
+        assert_eq!(report.entries, 4);
+        assert_eq!(report.failures.iter().map(|failure| failure.line).collect::<Vec<usize>>(), vec![2, 4, 5]);
+        assert_eq!(report.failing_letters, vec![('b', 2), ('c', 1)]);
+        assert_eq!(report.lengths.into_iter().collect::<Vec<(usize, usize)>>(), vec![(5, 3), (9, 1)]);
+
+        let error = day.load_records(&input, LoadMode::Strict).unwrap_err();
+        assert!(error.to_string().starts_with("text:3: record 3 'x' is invalid"), "{}", error);
+    }
+
+    #[test]
+    fn it_renders_reports() {
+        let (records, _) = Day2::default().load_records(&Input::Example(0), LoadMode::Strict).unwrap();
+        let report = audit(records);
+        let text = report.to_string();
+        assert!(text.contains("    2  b       cdefg      count 0 not in 1-3, neither position 1 nor 3 matches"));
+        assert!(text.ends_with("Most common failing letters : b (1), c (1)\nPassword lengths : 5 (2), 9 (1)"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["failures"][1]["line"], 3);
+        assert_eq!(json["failures"][1]["violations"][0]["policy"], "positions");
+        assert_eq!(json["failures"][1]["violations"][0]["matching"], serde_json::json!([2, 9]));
+    }
+}
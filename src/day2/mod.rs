@@ -1,10 +1,14 @@
 use std::str::FromStr;
 use crate::utils::GenericError;
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 
+pub mod policy;
+pub mod audit;
+
+/// A password and the policy it was made with, letters being Unicode chars (a combining accent counts as one)
 #[derive(Debug)]
 pub struct PasswordRequirement {
-    letter: String,
+    letter: char,
     min: usize,
     max: usize,
     password: String,
@@ -14,52 +18,56 @@ impl FromStr for PasswordRequirement {
     type Err = GenericError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let spec = String::from(s);
+        let (requirement, password) = s.split_once(':')
+            .ok_or_else(|| GenericError::parse("Missing ':' before the password".to_string()))?;
 
-        let spec_parts: Vec<&str> = spec.split(":").collect();
-        if spec_parts.len() < 2 {
-            return Err(GenericError::new("Not enough spec parts".to_string()));
-        }
+        let (min_max, letter) = requirement.trim().split_once(char::is_whitespace)
+            .ok_or_else(|| GenericError::parse("Missing letter after the range".to_string()))?;
 
-        let requirement_parts: Vec<&str> = spec_parts[0].split_whitespace().collect();
-        if requirement_parts.len() < 2 {
-            return Err(GenericError::new("Not enough requirement parts".to_string()));
-        }
-
-        let min_max_parts: Vec<&str> = requirement_parts[0].split("-").collect();
-        if min_max_parts.len() < 2 {
-            return Err(GenericError::new("Not enough minmax parts".to_string()));
-        }
+        let (min, max) = min_max.split_once('-')
+            .ok_or_else(|| GenericError::parse("Missing '-' in the range".to_string()))?;
 
-        let letter = requirement_parts[1].trim().to_string();
-        if letter.len() != 1 {
-            return Err(GenericError::new("Letter length is not correct".to_string()));
+        let mut letter_chars = letter.trim().chars();
+        let letter = match (letter_chars.next(), letter_chars.next()) {
+            (Some(letter), None) => letter,
+            _ => return Err(GenericError::parse(format!("'{}' is not a single letter", letter.trim()))),
         };
 
         Ok(PasswordRequirement {
             letter,
-            min: min_max_parts[0].trim().parse()?,
-            max: min_max_parts[1].trim().parse()?,
-            password: spec_parts[1].trim().to_string(),
+            min: min.trim().parse()?,
+            max: max.trim().parse()?,
+            password: password.trim().to_string(),
         })
     }
 }
 
 impl PasswordRequirement {
-    pub fn is_valid(&self) -> bool {
-        let count_of_letters = self.password.chars().into_iter()
-            .filter(|c| c.to_string() == self.letter)
-            .collect::<Vec<char>>()
-            .len();
+    /// Occurrences of a letter in the password
+    pub fn count_letter(&self, letter: char) -> usize {
+        self.password.chars().filter(|c| *c == letter).count()
+    }
 
-        count_of_letters >= self.min && count_of_letters <= self.max
+    /// Letter of the password at a position starting at 1, none beyond its end
+    pub fn get_letter(&self, position: usize) -> Option<char> {
+        position.checked_sub(1).and_then(|index| self.password.chars().nth(index))
     }
 
-    pub fn is_valid_part2(&self) -> bool {
-        let has_first = self.password.as_str()[self.min - 1..self.min] == self.letter;
-        let has_second = self.password.as_str()[self.max - 1..self.max] == self.letter;
+    pub fn is_valid(&self) -> bool {
+        (self.min..=self.max).contains(&self.count_letter(self.letter))
+    }
 
-        has_first ^ has_second
+    /// Whether the letter at a position starting at 1 is the given one, failing beyond the end of the password
+    pub fn has_letter_at(&self, position: usize, letter: char) -> Result<bool, GenericError> {
+        self.get_letter(position)
+            .map(|found| found == letter)
+            .ok_or_else(|| GenericError::Validation(format!("Position {} is not within the {} letters of '{}'",
+                                                            position, self.password.chars().count(), self.password)))
+    }
+
+    /// Whether exactly one of the positions holds the letter, both positions being within the password
+    pub fn is_valid_part2(&self) -> Result<bool, GenericError> {
+        Ok(self.has_letter_at(self.min, self.letter)? ^ self.has_letter_at(self.max, self.letter)?)
     }
 }
 
@@ -72,19 +80,105 @@ impl DailyChallenge for Day2 {
 
     fn get_day_num(&self) -> usize { 2 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        let count = data.into_iter()
-            .filter(|item| item.is_valid())
-            .collect::<Vec<&PasswordRequirement>>()
-            .len();
-        Ok(format!("{} valid passwords", count))
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("2"),
+                part_2: Some("1"),
+            },
+        ]
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        let count = data.iter().filter(|item| item.is_valid()).count();
+        Ok(Answer::new(count).with_explanation("valid passwords".to_string()))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        let count = data.into_iter()
-            .filter(|item| item.is_valid_part2())
-            .collect::<Vec<&PasswordRequirement>>()
-            .len();
-        Ok(format!("{} valid passwords", count))
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        let mut count = 0;
+        for item in data {
+            if item.is_valid_part2()? {
+                count += 1;
+            }
+        }
+        Ok(Answer::new(count).with_explanation("valid passwords".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day2::PasswordRequirement;
+    use crate::utils::{GenericError, XorShift};
+
+    /// Chars of every UTF-8 width, the separators of the format and a combining accent
+    const ALPHABET: [char; 16] = ['a', 'z', '0', '9', '-', ':', ' ', '\t', 'é', 'ß', 'Ж', '日', '€', '🎄', '\u{301}', '\u{0}'];
+
+    fn random_chars(rng: &mut XorShift, max_length: u64, alphabet: &[char]) -> Vec<char> {
+        (0..rng.next_below(max_length + 1))
+            .map(|_| alphabet[rng.next_below(alphabet.len() as u64) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn it_parses_unicode_lines() {
+        let requirement: PasswordRequirement = "1-2 日: 日本".parse().unwrap();
+        assert_eq!(requirement.letter, '日');
+        assert!(requirement.is_valid());
+        assert!(requirement.is_valid_part2().unwrap());
+
+        let requirement: PasswordRequirement = "1-3 é: éaé: x".parse().unwrap();
+        assert_eq!(requirement.password, "éaé: x");
+        assert!(!requirement.is_valid_part2().unwrap());
+
+        for invalid in &["1-3 ab: abc", "1-3: abc", "13 a: abc", "1-3 a abc", "-1-3 a: abc", ""] {
+            assert!(invalid.parse::<PasswordRequirement>().is_err(), "'{}' should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn it_rejects_positions_out_of_the_password() {
+        for line in &["0-1 a: abc", "1-4 a: abc", "2-5 é: éé"] {
+            let requirement: PasswordRequirement = line.parse().unwrap();
+            assert!(matches!(requirement.is_valid_part2(), Err(GenericError::Validation(_))), "'{}' should fail", line);
+        }
+    }
+
+    #[test]
+    fn it_never_panics_on_arbitrary_lines() {
+        let mut rng = XorShift::new(2);
+        for _ in 0..5000 {
+            let line: String = random_chars(&mut rng, 24, &ALPHABET).into_iter().collect();
+            let line = format!("{}{}", ["", "1-3 a: ", "2-1 ", "0-9 日:"][rng.next_below(4) as usize], line);
+            if let Ok(requirement) = line.parse::<PasswordRequirement>() {
+                requirement.is_valid();
+                let _ = requirement.is_valid_part2();
+            }
+        }
+    }
+
+    #[test]
+    fn it_checks_arbitrary_passwords() {
+        let letters: Vec<char> = ALPHABET.iter().copied().filter(|c| !c.is_whitespace() && *c != ':').collect();
+        let mut rng = XorShift::new(22);
+        for _ in 0..5000 {
+            let password = random_chars(&mut rng, 12, &letters);
+            let letter = letters[rng.next_below(letters.len() as u64) as usize];
+            let (min, max) = (rng.next_below(password.len() as u64 + 2) as usize, rng.next_below(password.len() as u64 + 2) as usize);
+            let line = format!("{}-{} {}: {}", min, max, letter, password.iter().collect::<String>());
+            let requirement: PasswordRequirement = line.parse().unwrap();
+
+            let count = password.iter().filter(|c| **c == letter).count();
+            assert_eq!(requirement.is_valid(), min <= count && count <= max, "{}", line);
+
+            let is_within = |position: usize| (1..=password.len()).contains(&position);
+            match requirement.is_valid_part2() {
+                Ok(is_valid) => {
+                    assert!(is_within(min) && is_within(max), "{}", line);
+                    assert_eq!(is_valid, (password[min - 1] == letter) ^ (password[max - 1] == letter), "{}", line);
+                }
+                Err(_) => assert!(!is_within(min) || !is_within(max), "{}", line),
+            }
+        }
     }
 }
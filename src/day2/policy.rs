@@ -0,0 +1,479 @@
+//! Password policies written in a small rule language, such as `count(letter) in min..max`.
+//!
+//! Rules are made of:
+//! - `count(<letter>) in <n>..<n>`, both ends included, or `count(<letter>) <comparison> <n>`
+//! - `pos(<n>) == <letter>` or `pos(<n>) != <letter>`, positions starting at 1, which cannot be checked on
+//!   passwords too short to have them
+//! - `len <comparison> <n>`
+//! - `contains(<text>)`
+//! - `not`, `and`, `xor` and `or`, from the tightest to the loosest, and parentheses
+//!
+//! `letter`, `min` and `max` stand for the requirement of each entry, comparisons are `==`, `!=`, `<`, `<=`,
+//! `>` and `>=`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::day2::PasswordRequirement;
+use crate::utils::GenericError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn compare(&self, left: usize, right: usize) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// A number of a rule, either written in it or taken from the requirement of each entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Literal(usize),
+    Min,
+    Max,
+}
+
+impl Value {
+    fn resolve(&self, entry: &PasswordRequirement) -> usize {
+        match self {
+            Value::Literal(value) => *value,
+            Value::Min => entry.min,
+            Value::Max => entry.max,
+        }
+    }
+}
+
+/// A letter of a rule, either written in it or the one required by each entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Letter {
+    Literal(char),
+    Required,
+}
+
+impl Letter {
+    fn resolve(&self, entry: &PasswordRequirement) -> char {
+        match self {
+            Letter::Literal(letter) => *letter,
+            Letter::Required => entry.letter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    CountIn { letter: Letter, min: Value, max: Value },
+    Count { letter: Letter, comparison: Comparison, value: Value },
+    /// Whether the letter at a position is, or is not, the given one
+    Position { position: Value, letter: Letter, is_equal: bool },
+    Length { comparison: Comparison, value: Value },
+    Contains(String),
+    Not(Box<Policy>),
+    And(Box<Policy>, Box<Policy>),
+    Xor(Box<Policy>, Box<Policy>),
+    Or(Box<Policy>, Box<Policy>),
+}
+
+impl Policy {
+    /// Whether the entry follows the policy, failing as day 2 part 2 does on positions beyond the password
+    pub fn is_satisfied(&self, entry: &PasswordRequirement) -> Result<bool, GenericError> {
+        let count = |letter: &Letter| entry.count_letter(letter.resolve(entry));
+
+        Ok(match self {
+            Policy::CountIn { letter, min, max } => (min.resolve(entry)..=max.resolve(entry)).contains(&count(letter)),
+            Policy::Count { letter, comparison, value } => comparison.compare(count(letter), value.resolve(entry)),
+            Policy::Position { position, letter, is_equal } =>
+                entry.has_letter_at(position.resolve(entry), letter.resolve(entry))? == *is_equal,
+            Policy::Length { comparison, value } => comparison.compare(entry.password.chars().count(), value.resolve(entry)),
+            Policy::Contains(text) => entry.password.contains(text.as_str()),
+            Policy::Not(policy) => !policy.is_satisfied(entry)?,
+            Policy::And(left, right) => left.is_satisfied(entry)? && right.is_satisfied(entry)?,
+            Policy::Xor(left, right) => left.is_satisfied(entry)? ^ right.is_satisfied(entry)?,
+            Policy::Or(left, right) => left.is_satisfied(entry)? || right.is_satisfied(entry)?,
+        })
+    }
+}
+
+impl FromStr for Policy {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, position: 0 };
+        let policy = parser.parse_or()?;
+        parser.skip_whitespace();
+        if parser.position < s.len() {
+            return Err(parser.error("the end of the rule"));
+        }
+
+        Ok(policy)
+    }
+}
+
+/// Recursive descent over the text of a rule, one method per precedence level
+struct Parser<'a> {
+    input: &'a str,
+    /// Byte offset of the next char to read
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, expected: &str) -> GenericError {
+        GenericError::parse(format!("Invalid policy '{}': expected {} at column {}",
+                                    self.input, expected, self.input[..self.position].chars().count() + 1))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.position = self.input.len() - self.rest().trim_start().len();
+    }
+
+    /// Consume a symbol when the rule continues with it
+    fn eat(&mut self, symbol: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(symbol) {
+            self.position += symbol.len();
+            return true;
+        }
+
+        false
+    }
+
+    /// Consume a word when the rule continues with it, and not with a longer word
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let is_whole_word = self.rest().strip_prefix(keyword)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'));
+        if is_whole_word {
+            self.position += keyword.len();
+        }
+
+        is_whole_word
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), GenericError> {
+        match self.eat(symbol) {
+            true => Ok(()),
+            false => Err(self.error(&format!("'{}'", symbol))),
+        }
+    }
+
+    /// The text of a function argument, up to its closing parenthesis
+    fn parse_argument(&mut self) -> Result<&'a str, GenericError> {
+        self.expect("(")?;
+        let length = self.rest().find(')').ok_or_else(|| self.error("')'"))?;
+        let argument = &self.rest()[..length];
+        self.position += length + 1;
+
+        Ok(argument)
+    }
+
+    fn parse_letter(&self, argument: &str) -> Result<Letter, GenericError> {
+        let mut chars = argument.trim().chars();
+        match (argument.trim(), chars.next(), chars.next()) {
+            ("letter", _, _) => Ok(Letter::Required),
+            (_, Some(letter), None) => Ok(Letter::Literal(letter)),
+            _ => Err(self.error("a single letter or 'letter'")),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, GenericError> {
+        if self.eat_keyword("min") {
+            return Ok(Value::Min);
+        }
+        if self.eat_keyword("max") {
+            return Ok(Value::Max);
+        }
+
+        let length = self.rest().find(|c: char| !c.is_ascii_digit()).unwrap_or(self.rest().len());
+        let value = self.rest()[..length].parse().map_err(|_| self.error("a number, 'min' or 'max'"))?;
+        self.position += length;
+
+        Ok(Value::Literal(value))
+    }
+
+    fn parse_comparison(&mut self) -> Result<Comparison, GenericError> {
+        let comparisons = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+
+        comparisons.iter()
+            .find(|(symbol, _)| self.eat(symbol))
+            .map(|(_, comparison)| *comparison)
+            .ok_or_else(|| self.error("a comparison"))
+    }
+
+    fn parse_or(&mut self) -> Result<Policy, GenericError> {
+        let mut policy = self.parse_xor()?;
+        while self.eat_keyword("or") {
+            policy = Policy::Or(Box::new(policy), Box::new(self.parse_xor()?));
+        }
+
+        Ok(policy)
+    }
+
+    fn parse_xor(&mut self) -> Result<Policy, GenericError> {
+        let mut policy = self.parse_and()?;
+        while self.eat_keyword("xor") {
+            policy = Policy::Xor(Box::new(policy), Box::new(self.parse_and()?));
+        }
+
+        Ok(policy)
+    }
+
+    fn parse_and(&mut self) -> Result<Policy, GenericError> {
+        let mut policy = self.parse_not()?;
+        while self.eat_keyword("and") {
+            policy = Policy::And(Box::new(policy), Box::new(self.parse_not()?));
+        }
+
+        Ok(policy)
+    }
+
+    fn parse_not(&mut self) -> Result<Policy, GenericError> {
+        if self.eat_keyword("not") {
+            return Ok(Policy::Not(Box::new(self.parse_not()?)));
+        }
+        if self.eat("(") {
+            let policy = self.parse_or()?;
+            self.expect(")")?;
+            return Ok(policy);
+        }
+
+        self.parse_rule()
+    }
+
+    fn parse_rule(&mut self) -> Result<Policy, GenericError> {
+        if self.eat_keyword("count") {
+            let letter = self.parse_argument().and_then(|argument| self.parse_letter(argument))?;
+            if self.eat_keyword("in") {
+                let min = self.parse_value()?;
+                self.expect("..")?;
+                return Ok(Policy::CountIn { letter, min, max: self.parse_value()? });
+            }
+            let comparison = self.parse_comparison()?;
+            return Ok(Policy::Count { letter, comparison, value: self.parse_value()? });
+        }
+        if self.eat_keyword("pos") {
+            let argument = self.parse_argument()?;
+            let mut inner = Parser { input: argument.trim(), position: 0 };
+            let position = inner.parse_value().map_err(|_| self.error("a position"))?;
+            if inner.position < inner.input.len() {
+                return Err(self.error("a position"));
+            }
+            let is_equal = match self.parse_comparison()? {
+                Comparison::Equal => true,
+                Comparison::NotEqual => false,
+                _ => return Err(self.error("'==' or '!='")),
+            };
+            self.skip_whitespace();
+            let length = self.rest().find(|c: char| c.is_whitespace() || c == ')').unwrap_or(self.rest().len());
+            let letter = self.parse_letter(&self.rest()[..length])?;
+            self.position += length;
+            return Ok(Policy::Position { position, letter, is_equal });
+        }
+        if self.eat_keyword("len") {
+            let comparison = self.parse_comparison()?;
+            return Ok(Policy::Length { comparison, value: self.parse_value()? });
+        }
+        if self.eat_keyword("contains") {
+            return Ok(Policy::Contains(self.parse_argument()?.to_string()));
+        }
+
+        Err(self.error("a rule"))
+    }
+}
+
+/// Policies checked together, each one with its source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicySet {
+    rules: Vec<(String, Policy)>,
+}
+
+impl FromStr for PolicySet {
+    type Err = GenericError;
+
+    /// One rule per line, blank lines and lines starting with # being ignored
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rules = s.lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| line.parse()
+                .map(|policy| (line.to_string(), policy))
+                .map_err(|err| GenericError::parse(format!("Line {}: {}", index + 1, err))))
+            .collect::<Result<Vec<(String, Policy)>, GenericError>>()?;
+
+        Ok(PolicySet { rules })
+    }
+}
+
+impl PolicySet {
+    pub fn load(path: &str) -> Result<Self, GenericError> {
+        std::fs::read_to_string(path)
+            .map_err(|err| GenericError::Io { path: Some(path.to_string()), source: err })?
+            .parse()
+    }
+
+    pub fn check(&self, entries: &[PasswordRequirement]) -> PolicyReport {
+        let outcomes: Vec<Vec<Result<bool, GenericError>>> = entries.iter()
+            .map(|entry| self.rules.iter().map(|(_, policy)| policy.is_satisfied(entry)).collect())
+            .collect();
+
+        let rules = self.rules.iter()
+            .enumerate()
+            .map(|(index, (source, _))| RuleReport {
+                source: source.clone(),
+                violations: outcomes.iter().filter(|outcome| matches!(outcome[index], Ok(false))).count(),
+                errors: outcomes.iter().filter(|outcome| outcome[index].is_err()).count(),
+            })
+            .collect();
+        let compliant = outcomes.iter()
+            .filter(|outcome| outcome.iter().all(|is_satisfied| matches!(is_satisfied, Ok(true))))
+            .count();
+        let first_error = outcomes.iter()
+            .enumerate()
+            .find_map(|(index, outcome)| outcome.iter().find_map(|is_satisfied| is_satisfied.as_ref().err())
+                .map(|err| format!("Entry {}: {}", index + 1, err)));
+
+        PolicyReport { entries: entries.len(), compliant, rules, first_error }
+    }
+}
+
+/// How many entries violate a rule, and on how many it cannot be checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleReport {
+    pub source: String,
+    pub violations: usize,
+    /// Entries the rule fails on, such as those too short for one of its positions
+    pub errors: usize,
+}
+
+/// How many entries violate each rule of a set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyReport {
+    pub entries: usize,
+    /// Entries satisfying every rule, without error
+    pub compliant: usize,
+    pub rules: Vec<RuleReport>,
+    /// The first entry a rule fails on, with the reason
+    pub first_error: Option<String>,
+}
+
+impl Display for PolicyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self.rules.iter().map(|rule| rule.source.chars().count()).max().unwrap_or(0).max(4);
+        writeln!(f, "{:<width$}  violations  errors", "rule", width = width)?;
+        for rule in &self.rules {
+            writeln!(f, "{:<width$}  {:>10}  {:>6}", rule.source, rule.violations, rule.errors, width = width)?;
+        }
+        write!(f, "{} entries, {} compliant with every rule", self.entries, self.compliant)?;
+        if let Some(error) = &self.first_error {
+            write!(f, "\nFirst error : {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day2::PasswordRequirement;
+    use crate::day2::policy::{Policy, PolicySet, Letter, Value, Comparison};
+    use crate::utils::GenericError;
+
+    fn is_satisfied(policy: &str, entry: &str) -> bool {
+        let entry: PasswordRequirement = entry.parse().unwrap();
+        policy.parse::<Policy>().unwrap().is_satisfied(&entry).unwrap()
+    }
+
+    #[test]
+    fn it_parses_rules() {
+        assert_eq!("count(a) in 1..3".parse::<Policy>().unwrap(),
+                   Policy::CountIn { letter: Letter::Literal('a'), min: Value::Literal(1), max: Value::Literal(3) });
+        assert_eq!("len>=8".parse::<Policy>().unwrap(),
+                   Policy::Length { comparison: Comparison::GreaterOrEqual, value: Value::Literal(8) });
+        assert_eq!("not contains(xyz)".parse::<Policy>().unwrap(),
+                   Policy::Not(Box::new(Policy::Contains("xyz".to_string()))));
+
+        for invalid in &["", "count(ab) > 1", "len => 3", "pos(1) < a", "len > 3 and", "(len > 3", "length > 3",
+                         "count(a) in 1-3", "contains(a", "pos(1x) == a", "pos(max junk) == letter"] {
+            assert!(invalid.parse::<Policy>().is_err(), "'{}' should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn it_checks_rules() {
+        assert!(is_satisfied("count(a) in 1..3", "1-3 a: abcde"));
+        assert!(!is_satisfied("count(letter) in min..max", "1-3 b: cdefg"));
+        assert!(is_satisfied("count(c) == 9", "2-9 c: ccccccccc"));
+        assert!(is_satisfied("pos(1)==a xor pos(3)==a", "1-3 a: abcde"));
+        assert!(!is_satisfied("pos(min)==letter xor pos(max)==letter", "2-9 c: ccccccccc"));
+        assert!(is_satisfied("pos(9) == c and pos(8) != d", "2-9 c: ccccccccc"));
+        assert!(is_satisfied("len > 3 or pos(12) == c", "2-9 c: ccccccccc"));
+        assert!(is_satisfied("len>=8 and not contains(xyz)", "2-9 c: ccccccccc"));
+        assert!(!is_satisfied("not contains(cde)", "1-3 b: cdefg"));
+    }
+
+    #[test]
+    fn it_combines_rules_by_precedence() {
+        assert!(is_satisfied("len < 3 and len > 8 or contains(c)", "1-3 b: cdefg"));
+        assert!(!is_satisfied("len < 3 and (len > 8 or contains(c))", "1-3 b: cdefg"));
+        assert!(is_satisfied("not len < 3 and not len > 8", "1-3 b: cdefg"));
+        assert!(!is_satisfied("contains(c) xor contains(d) or contains(z)", "1-3 b: cdefg"));
+    }
+
+    #[test]
+    fn it_reports_violations() {
+        let policies: PolicySet = "# Both parts\ncount(letter) in min..max\n\npos(min)==letter xor pos(max)==letter\n"
+            .parse().unwrap();
+        let entries: Vec<PasswordRequirement> = include_str!("example.txt").lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+
+        let report = policies.check(&entries);
+        let violations: Vec<(&str, usize)> = report.rules.iter().map(|rule| (rule.source.as_str(), rule.violations)).collect();
+        assert_eq!(violations, vec![("count(letter) in min..max", 1), ("pos(min)==letter xor pos(max)==letter", 2)]);
+        assert_eq!(report.compliant, 1);
+        assert_eq!(report.first_error, None);
+        assert!(report.to_string().ends_with("3 entries, 1 compliant with every rule"));
+
+        let error = "len > 1\nlen >> 1".parse::<PolicySet>().unwrap_err();
+        assert!(error.to_string().starts_with("Line 2: "));
+    }
+
+    #[test]
+    fn it_reports_positions_out_of_the_password() {
+        let entry: PasswordRequirement = "1-12 é: é".parse().unwrap();
+        assert!(matches!("pos(12) != é".parse::<Policy>().unwrap().is_satisfied(&entry), Err(GenericError::Validation(_))));
+        assert!(matches!("pos(0) == é".parse::<Policy>().unwrap().is_satisfied(&entry), Err(GenericError::Validation(_))));
+        assert!(entry.is_valid_part2().is_err());
+
+        let policies: PolicySet = "len < 3\npos(min)==letter xor pos(max)==letter".parse().unwrap();
+        let entries: Vec<PasswordRequirement> = ["1-3 a: abcde", "1-12 é: é"].iter().map(|line| line.parse().unwrap()).collect();
+        let report = policies.check(&entries);
+        assert_eq!(report.rules[1].errors, 1);
+        assert_eq!((report.rules[1].violations, report.compliant), (0, 0));
+        assert_eq!(report.first_error.unwrap(), "Entry 2: Position 12 is not within the 1 letters of 'é'");
+    }
+}
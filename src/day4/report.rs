@@ -0,0 +1,85 @@
+use std::fmt::{Display, Formatter};
+use crate::day4::PassportBuilder;
+use crate::day4::schema::{Schema, Validation};
+use crate::utils::Records;
+
+/// A passport failing its schema, located by its record in the batch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPassport {
+    /// 1-based index of the record, as in load errors
+    pub index: usize,
+    pub validation: Validation,
+}
+
+/// Invalid passports of a batch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchReport {
+    pub passports: usize,
+    pub invalid: Vec<InvalidPassport>,
+}
+
+/// Validate every passport of a batch file, located by its record
+pub fn diagnose(records: Records<PassportBuilder>, schema: &Schema) -> BatchReport {
+    let passports = records.len();
+    let invalid = records.into_iter()
+        .map(|(passport, position)| InvalidPassport { index: position.index, validation: passport.validate(schema) })
+        .filter(|passport| !passport.validation.is_valid())
+        .collect();
+
+    BatchReport { passports, invalid }
+}
+
+impl Display for BatchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for passport in &self.invalid {
+            writeln!(f, "Record {}", passport.index)?;
+            for outcome in passport.validation.get_failures() {
+                writeln!(f, "  - {}", outcome)?;
+            }
+        }
+        write!(f, "{} passports, {} valid, {} invalid",
+               self.passports, self.passports - self.invalid.len(), self.invalid.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day4::Day4;
+    use crate::day4::report::{diagnose, BatchReport};
+    use crate::daily_challenge::{DailyChallenge, Input};
+    use crate::utils::LoadMode;
+
+    fn load(day: &Day4, input: &Input, mode: LoadMode) -> BatchReport {
+        let (records, _) = day.load_records(input, mode).unwrap();
+        diagnose(records, day.get_schema())
+    }
+
+    #[test]
+    fn it_reports_invalid_passports() {
+        let day = Day4::default();
+        let report = load(&day, &Input::Example(1), LoadMode::Strict);
+        assert_eq!(report.passports, 4);
+        assert_eq!(report.invalid.iter().map(|passport| passport.index).collect::<Vec<usize>>(), vec![1, 2, 3, 4]);
+
+        let report = load(&day, &Input::Example(2), LoadMode::Strict);
+        assert!(report.invalid.is_empty());
+        assert!(report.to_string().ends_with("4 passports, 4 valid, 0 invalid"));
+    }
+
+    #[test]
+    fn it_locates_failures() {
+        let day = Day4::default();
+        let batch = Input::Text("byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704\n\n\n\n\
+                                 byr:19x0 iyr:2012 eyr:2030 hgt:190in hcl:#623a2f ecl:grn\n".to_string());
+        let report = load(&day, &batch, LoadMode::Lenient);
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.to_string(), "Record 3\n\
+            \x20 - byr: malformed '19x0', not an integer\n\
+            \x20 - hgt: out of range '190in', outside 59-76in\n\
+            \x20 - pid: missing\n\
+            2 passports, 1 valid, 1 invalid");
+
+        let error = day.load_records(&batch, LoadMode::Strict).unwrap_err();
+        assert!(error.to_string().starts_with("text:3: record 2 '' is invalid"), "{}", error);
+    }
+}
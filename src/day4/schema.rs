@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::str::FromStr;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use crate::utils::GenericError;
+
+/// Inclusive bounds of an integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Range {
+    /// Check an integer, `unit` following the bounds in messages
+    fn check(&self, value: &str, unit: &str) -> FieldStatus {
+        match value.parse::<i64>() {
+            Ok(number) if (self.min..=self.max).contains(&number) => FieldStatus::Ok,
+            Ok(_) => FieldStatus::OutOfRange(format!("outside {}-{}{}", self.min, self.max, unit)),
+            Err(_) => FieldStatus::Malformed("not an integer".to_string()),
+        }
+    }
+}
+
+/// Outcome of the validation of a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldStatus {
+    Ok,
+    /// A required field is absent
+    Missing,
+    /// The value does not have the expected shape, such as an integer that does not parse
+    Malformed(String),
+    /// The value has the expected shape, but is not allowed
+    OutOfRange(String),
+}
+
+/// Outcome of one field of a document, with its value when present
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldOutcome {
+    pub name: String,
+    pub value: Option<String>,
+    pub status: FieldStatus,
+}
+
+impl Display for FieldOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = self.value.as_deref().unwrap_or("");
+        match &self.status {
+            FieldStatus::Ok => write!(f, "{}: ok '{}'", self.name, value),
+            FieldStatus::Missing => write!(f, "{}: missing", self.name),
+            FieldStatus::Malformed(reason) => write!(f, "{}: malformed '{}', {}", self.name, value, reason),
+            FieldStatus::OutOfRange(reason) => write!(f, "{}: out of range '{}', {}", self.name, value, reason),
+        }
+    }
+}
+
+/// Outcome of every field declared by a schema, in its order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validation {
+    pub fields: Vec<FieldOutcome>,
+}
+
+impl Validation {
+    pub fn is_valid(&self) -> bool {
+        self.fields.iter().all(|field| field.status == FieldStatus::Ok)
+    }
+
+    pub fn get_failures(&self) -> impl Iterator<Item=&FieldOutcome> {
+        self.fields.iter().filter(|field| field.status != FieldStatus::Ok)
+    }
+}
+
+/// A check of the value of a field, as declared in a schema
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Validator {
+    Range {
+        #[serde(flatten)]
+        range: Range,
+    },
+    Regex {
+        #[serde(deserialize_with = "deserialize_regex")]
+        pattern: Regex,
+    },
+    /// An integer directly followed by a unit, each unit having its own range
+    Units { units: BTreeMap<String, Range> },
+    Enum { values: Vec<String> },
+}
+
+fn deserialize_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+impl Validator {
+    pub fn check(&self, value: &str) -> FieldStatus {
+        match self {
+            Validator::Range { range } => range.check(value, ""),
+            Validator::Regex { pattern } if pattern.is_match(value) => FieldStatus::Ok,
+            Validator::Regex { pattern } => FieldStatus::Malformed(format!("does not match {}", pattern)),
+            Validator::Units { units } => {
+                let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len()));
+                match units.get(unit) {
+                    Some(range) => range.check(number, unit),
+                    None => {
+                        let names: Vec<&str> = units.keys().map(|name| name.as_str()).collect();
+                        FieldStatus::Malformed(format!("unit should be one of {}", names.join(", ")))
+                    }
+                }
+            }
+            Validator::Enum { values } if values.iter().any(|allowed| allowed == value) => FieldStatus::Ok,
+            Validator::Enum { values } => FieldStatus::OutOfRange(format!("not one of {}", values.join(", "))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldRule {
+    pub name: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub validators: Vec<Validator>,
+}
+
+/// Fields of a document, in the order they are declared
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    pub fields: Vec<FieldRule>,
+}
+
+impl FromStr for Schema {
+    type Err = GenericError;
+
+    /// Parse a TOML schema, see `schema.toml`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|err| GenericError::parse(format!("Invalid schema: {}", err)))
+    }
+}
+
+impl Schema {
+    /// The passport rules of the puzzle
+    pub fn get_default() -> Result<Self, GenericError> {
+        include_str!("schema.toml").parse()
+    }
+
+    /// Load a schema written in TOML, or in JSON when the file ends with .json
+    pub fn load(path: &str) -> Result<Self, GenericError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| GenericError::Io { path: Some(path.to_string()), source: err })?;
+
+        match path.ends_with(".json") {
+            true => serde_json::from_str(&contents).map_err(|err| GenericError::parse(format!("{}: invalid schema: {}", path, err))),
+            false => contents.parse(),
+        }
+    }
+
+    /// Whether every required field is present, whatever its value
+    pub fn has_required_fields(&self, fields: &HashMap<String, String>) -> bool {
+        self.fields.iter()
+            .filter(|rule| rule.required)
+            .all(|rule| fields.contains_key(&rule.name))
+    }
+
+    /// Check every declared field, a present field getting the status of the first validator it fails
+    pub fn validate(&self, fields: &HashMap<String, String>) -> Validation {
+        let fields = self.fields.iter()
+            .map(|rule| {
+                let value = fields.get(&rule.name).cloned();
+                let status = match &value {
+                    Some(value) => rule.validators.iter()
+                        .map(|validator| validator.check(value))
+                        .find(|status| *status != FieldStatus::Ok)
+                        .unwrap_or(FieldStatus::Ok),
+                    None if rule.required => FieldStatus::Missing,
+                    None => FieldStatus::Ok,
+                };
+                FieldOutcome { name: rule.name.clone(), value, status }
+            })
+            .collect();
+
+        Validation { fields }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day4::schema::{Schema, Validator, FieldStatus};
+    use crate::utils::get_temp_path;
+    use std::collections::HashMap;
+
+    fn check(schema: &Schema, field: &str, value: &str) -> FieldStatus {
+        let document: HashMap<String, String> = vec![(field.to_string(), value.to_string())].into_iter().collect();
+        schema.validate(&document).fields.into_iter().find(|outcome| outcome.name == field).unwrap().status
+    }
+
+    fn is_valid(schema: &Schema, field: &str, value: &str) -> bool {
+        check(schema, field, value) == FieldStatus::Ok
+    }
+
+    fn document(fields: &[(&str, &str)]) -> HashMap<String, String> {
+        fields.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn it_ships_the_puzzle_rules() {
+        let schema = Schema::get_default().unwrap();
+        let names: Vec<&str> = schema.fields.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"]);
+
+        for (field, valid, invalid) in &[
+            ("byr", "2002", "2003"),
+            ("hgt", "60in", "190in"),
+            ("hgt", "190cm", "190"),
+            ("hcl", "#123abc", "#123abz"),
+            ("hcl", "#123abc", "123abc"),
+            ("ecl", "brn", "wat"),
+            ("pid", "000000001", "0123456789"),
+            ("cid", "anything", ""),
+        ] {
+            assert!(is_valid(&schema, field, valid), "{}:{} should be valid", field, valid);
+            if *field != "cid" {
+                assert!(!is_valid(&schema, field, invalid), "{}:{} should be invalid", field, invalid);
+            }
+        }
+    }
+
+    #[test]
+    fn it_checks_documents() {
+        let schema: Schema = r#"
+            [[fields]]
+            name = "size"
+            required = true
+            validators = [{ type = "units", units = { m = { min = 1, max = 3 } } }, { type = "regex", pattern = '^1' }]
+
+            [[fields]]
+            name = "colour"
+            validators = [{ type = "enum", values = ["red"] }]
+        "#.parse().unwrap();
+
+        assert!(schema.validate(&document(&[("size", "1m")])).is_valid());
+        assert!(!schema.validate(&document(&[("size", "2m")])).is_valid());
+        assert!(!schema.validate(&document(&[("size", "1m"), ("colour", "blue")])).is_valid());
+        assert!(!schema.has_required_fields(&document(&[("colour", "red")])));
+    }
+
+    #[test]
+    fn it_diagnoses_every_field() {
+        let schema = Schema::get_default().unwrap();
+        assert_eq!(check(&schema, "byr", "19x0"), FieldStatus::Malformed("not an integer".to_string()));
+        assert_eq!(check(&schema, "byr", "1900"), FieldStatus::OutOfRange("outside 1920-2002".to_string()));
+        assert_eq!(check(&schema, "hgt", "190in"), FieldStatus::OutOfRange("outside 59-76in".to_string()));
+        assert_eq!(check(&schema, "hgt", "190"), FieldStatus::Malformed("unit should be one of cm, in".to_string()));
+        assert!(matches!(check(&schema, "pid", "01234"), FieldStatus::Malformed(_)));
+        assert!(matches!(check(&schema, "ecl", "wat"), FieldStatus::OutOfRange(_)));
+
+        let validation = schema.validate(&document(&[("byr", "19x0"), ("iyr", "2015"), ("hgt", "60in")]));
+        assert_eq!(validation.fields.len(), 8);
+        let failures: Vec<String> = validation.get_failures().map(|outcome| outcome.to_string()).collect();
+        assert_eq!(failures, vec!["byr: malformed '19x0', not an integer", "eyr: missing", "hcl: missing", "ecl: missing", "pid: missing"]);
+        assert_eq!(validation.fields[1].to_string(), "iyr: ok '2015'");
+    }
+
+    #[test]
+    fn it_rejects_invalid_schemas() {
+        assert!("[[fields]]\nname = \"a\"\nvalidators = [{ type = \"regex\", pattern = \"(\" }]".parse::<Schema>().is_err());
+        assert!("[[fields]]\nname = \"a\"\nvalidators = [{ type = \"length\" }]".parse::<Schema>().is_err());
+        assert!("[[fields]]\nrequired = true".parse::<Schema>().is_err());
+        assert!("[[fields]]\nname = \"byr\"\nrequird = true".parse::<Schema>().is_err());
+        assert!("[[fields]]\nname = \"byr\"\nvalidators = [{ type = \"range\", mn = 1920, max = 2002 }]".parse::<Schema>().is_err());
+        assert!("[[fields]]\nname = \"hgt\"\nvalidators = [{ type = \"units\", units = { cm = { min = 1, max = 2, step = 1 } } }]"
+            .parse::<Schema>().is_err());
+        assert!("version = 2\n[[fields]]\nname = \"byr\"".parse::<Schema>().is_err());
+    }
+
+    #[test]
+    fn it_loads_json_schemas() {
+        let path = get_temp_path("schema.json");
+        std::fs::write(&path, r#"{"fields": [{"name": "byr", "required": true, "validators": [{"type": "range", "min": 1, "max": 2}]}]}"#)
+            .unwrap();
+
+        let schema = Schema::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(schema.fields[0].required);
+        assert!(matches!(schema.fields[0].validators[0], Validator::Range { .. }));
+    }
+}
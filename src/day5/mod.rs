@@ -1,7 +1,7 @@
 use std::str::FromStr;
 use crate::utils::GenericError;
 use std::cmp::Ordering;
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 
 #[derive(Debug, Eq)]
 pub struct BoardingPass {
@@ -22,27 +22,27 @@ impl FromStr for BoardingPass {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() != 10 {
-            return Err(GenericError::new("Invalid input".to_string()));
+            return Err(GenericError::parse("Invalid input".to_string()));
         }
 
         let mut row = 0;
         let mut column = 0;
 
-        for char in s.chars().into_iter() {
+        for char in s.chars() {
             match char {
-                'F' => row = row << 1,
+                'F' => row <<= 1,
                 'B' => row = (row << 1) + 1,
-                'L' => column = column << 1,
+                'L' => column <<= 1,
                 'R' => column = (column << 1) + 1,
-                _ => return Err(GenericError::new("Invalid char".to_string()))
+                _ => return Err(GenericError::parse("Invalid char".to_string()))
             };
         }
 
-        return Ok(BoardingPass {
+        Ok(BoardingPass {
             spec: s.to_string(),
             row,
             column,
-        });
+        })
     }
 }
 
@@ -87,23 +87,33 @@ impl DailyChallenge for Day5 {
 
     fn get_day_num(&self) -> usize { 5 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("820"),
+                part_2: None,
+            },
+        ]
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let day5_max = data.boarding_pass.last().unwrap();
-        Ok(format!("max seat is {}", day5_max.get_seat_id()))
+        Ok(Answer::new(day5_max.get_seat_id()).with_explanation("highest seat id".to_string()))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let mut day5_last_value = data.boarding_pass.first().unwrap().get_seat_id();
-        let day5_my_seat = (&data.boarding_pass).into_iter()
+        let day5_my_seat = data.boarding_pass.iter()
             .find(|boarding_pass| {
-                return if boarding_pass.get_seat_id() - day5_last_value > 1 {
+                if boarding_pass.get_seat_id() - day5_last_value > 1 {
                     true
                 } else {
                     day5_last_value = boarding_pass.get_seat_id();
                     false
-                };
+                }
             }).unwrap();
-        Ok(format!("my seat is {}", day5_my_seat.get_seat_id() - 1))
+        Ok(Answer::new(day5_my_seat.get_seat_id() - 1).with_explanation("my seat id".to_string()))
     }
 }
 
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::str::FromStr;
-use crate::utils::{GenericError, load_data};
-use crate::daily_challenge::DailyChallenge;
+use crate::utils::GenericError;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 
 pub struct Answers {
     all_answers: HashSet<String>,
@@ -13,10 +13,10 @@ impl FromStr for Answers {
     type Err = GenericError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let input_list: Vec<HashSet<String>> = s.split_whitespace().into_iter()
+        let input_list: Vec<HashSet<String>> = s.split_whitespace()
             .map(|substr| {
                 let mut set = HashSet::default();
-                for char in substr.chars().into_iter() {
+                for char in substr.chars() {
                     set.insert(char.to_string());
                 }
                 set
@@ -24,12 +24,12 @@ impl FromStr for Answers {
             .collect();
 
 
-        let union_chars: HashSet<String> = (&input_list).into_iter()
+        let union_chars: HashSet<String> = input_list.iter()
             .fold(HashSet::default(), |acc_set, cur_set| {
-                acc_set.union(&cur_set).cloned().collect()
+                acc_set.union(cur_set).cloned().collect()
             });
 
-        let first_input = match (&input_list).first() {
+        let first_input = match input_list.first() {
             Some(first_set) => first_set.clone(),
             None => HashSet::default()
         };
@@ -61,12 +61,12 @@ pub struct AnswersList {
 
 impl AnswersList {
     pub fn all_yes_count(&self) -> usize {
-        (&self.answers_list).into_iter()
+        self.answers_list.iter()
             .map(|answers| answers.all_yes_count())
             .sum()
     }
     pub fn shared_yes_count(&self) -> usize {
-        (&self.answers_list).into_iter()
+        self.answers_list.iter()
             .map(|answers| answers.shared_yes_count())
             .sum()
     }
@@ -87,18 +87,23 @@ impl DailyChallenge for Day6 {
 
     fn get_day_num(&self) -> usize { 6 }
 
-    fn load_data(&self, file_path: &str) -> Result<Self::Wrapper, GenericError>
-        where <Self::Data as std::str::FromStr>::Err: std::error::Error
-    {
-        let data: Vec<Self::Data> = load_data(file_path, "\n\n")?;
-        Ok(data.into())
+    fn get_split_pattern(&self) -> &str { "\n\n" }
+
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("11"),
+                part_2: Some("6"),
+            },
+        ]
     }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        Ok(format!("there is {} unique yes", data.all_yes_count()))
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        Ok(Answer::new(data.all_yes_count()).with_explanation("unique yes".to_string()))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        Ok(format!("there is {} shared answers", data.shared_yes_count()))
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        Ok(Answer::new(data.shared_yes_count()).with_explanation("shared answers".to_string()))
     }
 }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use crate::utils::GenericError;
 use regex::{Regex, Captures};
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 
 #[derive(Default, Debug, Clone)]
 pub struct BagSpec {
@@ -23,8 +23,8 @@ impl FromStr for BagSpec {
 
         let captures = main_regex.captures(s);
 
-        if let None = captures {
-            return Err(GenericError::new("Spec did not match regex".to_string()));
+        if captures.is_none() {
+            return Err(GenericError::parse("Spec did not match regex".to_string()));
         }
 
         let matches = captures.unwrap();
@@ -36,7 +36,7 @@ impl FromStr for BagSpec {
                 .split(", ")
                 .map(|dep_spec| match color_dep_regex.captures(dep_spec) {
                     Some(capture) => Ok(capture),
-                    None => Err(GenericError::new("Dep spec did not match".to_string()))
+                    None => Err(GenericError::parse("Dep spec did not match".to_string()))
                 })
                 .collect::<Result<Vec<Captures>, _>>()?;
 
@@ -62,13 +62,13 @@ impl BagSpec {
             return false;
         } else if self.sub_bags.contains_key(color.as_str()) {
             return true;
-        } else if self.sub_bags.len() == 0 {
+        } else if self.sub_bags.is_empty() {
             return false;
         } else if cache.contains_key(color.as_str()) {
-            return cache.get(color.as_str()).unwrap().clone();
+            return *cache.get(color.as_str()).unwrap();
         }
 
-        for (color_dep, _) in (&self.sub_bags).into_iter() {
+        for (color_dep, _) in self.sub_bags.iter() {
             let has_color = match list.bags.get(color_dep.as_str()) {
                 Some(bag) => bag.contains_color_recursive(color, list, cache),
                 None => false
@@ -81,18 +81,18 @@ impl BagSpec {
         }
 
         cache.insert(self.color.clone(), false);
-        return false;
+        false
     }
 
     pub fn sum_contains_recursive(&self, list: &BagList, cache: &mut HashMap<String, usize>) -> usize {
-        if self.sub_bags.len() == 0 {
+        if self.sub_bags.is_empty() {
             return 0;
         } else if cache.contains_key(self.color.as_str()) {
-            return cache.get(self.color.as_str()).unwrap().clone();
+            return *cache.get(self.color.as_str()).unwrap();
         }
 
         let mut sum = 0;
-        for (sub_bag_color, count) in (&self.sub_bags).into_iter() {
+        for (sub_bag_color, count) in self.sub_bags.iter() {
             let bag = list.bags.get(sub_bag_color.as_str()).unwrap();
             let sub_sum = bag.sum_contains_recursive(list, cache);
 
@@ -132,20 +132,35 @@ impl DailyChallenge for Day7 {
 
     fn get_day_num(&self) -> usize { 7 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("4"),
+                part_2: Some("32"),
+            },
+            Example {
+                input: include_str!("example2.txt"),
+                part_1: None,
+                part_2: Some("126"),
+            },
+        ]
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let mut cache = HashMap::default();
-        let matches: Vec<_> = (&data.bags).into_iter()
+        let matches: Vec<_> = data.bags.iter()
             .filter(|(_, bag)| bag.contains_color_recursive(&"shiny gold".to_string(), data, &mut cache))
             .collect();
 
-        Ok(format!("final count is {}", matches.len()))
+        Ok(Answer::new(matches.len()).with_explanation("bags that can contain a shiny gold bag".to_string()))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let mut cache = HashMap::default();
         let shiny_gold_bag = data.bags.get("shiny gold").unwrap();
 
-        Ok(format!("final count is {}", shiny_gold_bag.sum_contains_recursive(data, &mut cache)))
+        Ok(Answer::new(shiny_gold_bag.sum_contains_recursive(data, &mut cache)).with_explanation("bags inside the shiny gold bag".to_string()))
     }
 }
 
@@ -170,7 +185,7 @@ mod tests {
         ];
 
         let bag_specs: Result<Vec<BagSpec>, _> = specs.into_iter()
-            .map(|s| BagSpec::from_str(s))
+            .map(BagSpec::from_str)
             .collect();
 
         assert!(bag_specs.is_ok());
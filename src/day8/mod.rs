@@ -1,6 +1,6 @@
 use std::str::FromStr;
 use crate::utils::GenericError;
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RunCode {
@@ -59,9 +59,9 @@ impl Program {
             }
             RunCode::Jmp => {
                 if current_instruction.param.is_positive() {
-                    self.current_line += current_instruction.param.abs() as usize;
+                    self.current_line += current_instruction.param.unsigned_abs();
                 } else {
-                    self.current_line -= current_instruction.param.abs() as usize;
+                    self.current_line -= current_instruction.param.unsigned_abs();
                 }
             }
             RunCode::Nop => self.current_line += 1
@@ -96,7 +96,7 @@ impl FromStr for Instruction {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split(" ").collect();
         if parts.len() < 2 {
-            return GenericError::throw("No space in string");
+            return Err(GenericError::parse("No space in string".to_string()));
         }
 
         let name = parts[0].parse()?;
@@ -120,7 +120,7 @@ impl FromStr for RunCode {
         } else if s == "jmp" {
             Ok(RunCode::Jmp)
         } else {
-            GenericError::throw("Invalid instruction")
+            Err(GenericError::parse("Invalid instruction".to_string()))
         }
     }
 }
@@ -146,25 +146,35 @@ impl DailyChallenge for Day8 {
 
     fn get_day_num(&self) -> usize { 8 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("5"),
+                part_2: Some("8"),
+            },
+        ]
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         let mut program = data.clone();
         program.execute_until_loop(2)?;
-        Ok(format!("Accumulator value before first loop is {}", program.accumulator))
+        Ok(Answer::new(program.accumulator).with_explanation("accumulator value before the first loop".to_string()))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
         for (i, instruction) in data.instructions.iter().enumerate().rev() {
             if instruction.can_mutate() {
                 let mut program = data.clone();
-                program.instructions[i] = instruction.clone().mutate();
+                program.instructions[i] = (*instruction).mutate();
                 let result = program.execute_until_loop(2);
                 if let Err(ExitCode::EndOfProgram) = result {
-                    return Ok(format!("The mutation was on index {}, the program ends with value {}", i, program.accumulator));
+                    return Ok(Answer::new(program.accumulator).with_explanation(format!("accumulator value at the end, after mutating index {}", i)));
                 }
             }
         }
 
-        GenericError::throw("Could not find a valid mutation")
+        Err(GenericError::NoSolution("Could not find a valid mutation".to_string()))
     }
 }
 
@@ -188,7 +198,7 @@ mod tests {
         ];
 
         let instructions_result: Result<Vec<Instruction>, _> = input.into_iter()
-            .map(|s| Instruction::from_str(s))
+            .map(Instruction::from_str)
             .collect();
 
         assert!(instructions_result.is_ok());
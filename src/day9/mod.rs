@@ -1,4 +1,4 @@
-use crate::daily_challenge::DailyChallenge;
+use crate::daily_challenge::{DailyChallenge, Answer, Example};
 use crate::utils::GenericError;
 use std::str::FromStr;
 
@@ -10,7 +10,7 @@ impl Input {
         let len = previous_nums.len();
         for i in 0..len {
             for j in i + 1..len {
-                if &previous_nums[i].0 + &previous_nums[j].0 == self.0 {
+                if previous_nums[i].0 + previous_nums[j].0 == self.0 {
                     return true;
                 }
             }
@@ -28,14 +28,19 @@ impl FromStr for Input {
     }
 }
 
-fn find_bad_result(data: &Vec<Input>) -> Result<(usize, &Input), GenericError> {
-    for i in 25..data.len() {
-        let previous_nums = &data[i - 25..i];
+/// Numbers before the first one to check, in the input
+pub const PREAMBLE: usize = 25;
+/// Numbers before the first one to check, in the example of the statement
+pub const EXAMPLE_PREAMBLE: usize = 5;
+
+fn find_bad_result(data: &[Input], preamble: usize) -> Result<(usize, &Input), GenericError> {
+    for i in preamble..data.len() {
+        let previous_nums = &data[i - preamble..i];
         if !&data[i].is_valid(previous_nums) {
             return Ok((i, &data[i]));
         }
     }
-    GenericError::throw("No result found")
+    Err(GenericError::NoSolution("No result found".to_string()))
 }
 
 fn find_contiguous_sum(data: &[Input], target: usize) -> Result<&[Input], GenericError> {
@@ -51,11 +56,20 @@ fn find_contiguous_sum(data: &[Input], target: usize) -> Result<&[Input], Generi
             return Ok(&data[i..i + incr]);
         }
     }
-    GenericError::throw("No result found")
+    Err(GenericError::NoSolution("No result found".to_string()))
+}
+
+#[derive(Debug)]
+pub struct Day9 {
+    /// Numbers each one is checked against
+    preamble: usize,
 }
 
-#[derive(Debug, Default)]
-pub struct Day9;
+impl Default for Day9 {
+    fn default() -> Self {
+        Day9 { preamble: PREAMBLE }
+    }
+}
 
 impl DailyChallenge for Day9 {
     type Data = Input;
@@ -63,20 +77,32 @@ impl DailyChallenge for Day9 {
 
     fn get_day_num(&self) -> usize { 9 }
 
-    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        let bad_result = find_bad_result(data)?.1.0;
+    fn get_examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                input: include_str!("example.txt"),
+                part_1: Some("127"),
+                part_2: Some("62"),
+            },
+        ]
+    }
+
+    fn get_example_challenge(&self, _index: usize) -> Option<Self> {
+        Some(Day9 { preamble: EXAMPLE_PREAMBLE })
+    }
+
+    fn solve_part_1(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        let bad_result = find_bad_result(data, self.preamble)?.1.0;
 
-        Ok(format!("the first bad value is {}", bad_result))
+        Ok(Answer::new(bad_result).with_explanation("first invalid value".to_string()))
     }
 
-    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<String, GenericError> {
-        let bad_result = find_bad_result(data)?;
-        let mut contiguous_set: Vec<Input> = find_contiguous_sum(&data[..bad_result.0], bad_result.1.0)?.into_iter()
-            .map(|input| input.clone())
-            .collect();
+    fn solve_part_2(&self, data: &Self::Wrapper) -> Result<Answer, GenericError> {
+        let bad_result = find_bad_result(data, self.preamble)?;
+        let mut contiguous_set: Vec<Input> = find_contiguous_sum(&data[..bad_result.0], bad_result.1.0)?.to_vec();
         contiguous_set.sort();
         let weakness = contiguous_set.first().unwrap().0 + contiguous_set.last().unwrap().0;
 
-        Ok(format!("the weakness is {}", weakness))
+        Ok(Answer::new(weakness).with_explanation("encryption weakness".to_string()))
     }
 }
@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::BufWriter;
+use std::str::FromStr;
+use crate::utils::GenericError;
+
+/// Side of the square of pixels drawn for each cell
+const CELL_SIZE: usize = 4;
+/// Time each generation stays on screen in an animation, in hundredths of a second
+const GIF_DELAY: u16 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFormat {
+    Text,
+    Png,
+    Gif,
+}
+
+impl FromStr for FrameFormat {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(FrameFormat::Text),
+            "png" => Ok(FrameFormat::Png),
+            "gif" => Ok(FrameFormat::Gif),
+            _ => Err(GenericError::Usage(format!("Unknown frame format '{}'", s))),
+        }
+    }
+}
+
+impl FrameFormat {
+    /// Where frames are written when no output is given, stdout for text
+    pub fn get_default_output(&self, name: &str) -> Option<String> {
+        match self {
+            FrameFormat::Text => None,
+            FrameFormat::Png => Some(name.to_string()),
+            FrameFormat::Gif => Some(format!("{}.gif", name)),
+        }
+    }
+}
+
+/// One generation of a 2D grid, each cell being an index in a palette of RGB colours
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<u8>,
+}
+
+impl Frame {
+    fn get_pixel_size(&self) -> (usize, usize) {
+        (self.width * CELL_SIZE, self.height * CELL_SIZE)
+    }
+
+    /// Palette index of every pixel, row by row
+    fn get_pixels(&self) -> Vec<u8> {
+        let (pixel_width, pixel_height) = self.get_pixel_size();
+        (0..pixel_height)
+            .flat_map(|y| (0..pixel_width).map(move |x| (x, y)))
+            .map(|(x, y)| self.cells[(y / CELL_SIZE) * self.width + x / CELL_SIZE])
+            .collect()
+    }
+}
+
+/// Concatenate text generations, each one under its number
+pub fn render_text<T: ToString>(generations: &[T]) -> String {
+    generations.iter()
+        .enumerate()
+        .map(|(generation, frame)| format!("Generation {}\n{}\n\n", generation, frame.to_string()))
+        .collect()
+}
+
+fn create_file(path: &str) -> Result<BufWriter<File>, GenericError> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|err| GenericError::Io { path: Some(path.to_string()), source: err })
+}
+
+/// Write one PNG per frame, named `<prefix>-<generation>.png`, returns the written paths
+pub fn save_pngs(prefix: &str, frames: &[Frame], palette: &[[u8; 3]]) -> Result<Vec<String>, GenericError> {
+    let mut paths = vec![];
+    for (generation, frame) in frames.iter().enumerate() {
+        let path = format!("{}-{:04}.png", prefix, generation);
+        let (pixel_width, pixel_height) = frame.get_pixel_size();
+        let data: Vec<u8> = frame.get_pixels().into_iter()
+            .flat_map(|index| palette[index as usize].iter().copied())
+            .collect();
+
+        let mut encoder = png::Encoder::new(create_file(&path)?, pixel_width as u32, pixel_height as u32);
+        encoder.set_color(png::ColorType::RGB);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .map_err(|err| GenericError::new(format!("{}: {}", path, err)))?;
+
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Write every frame in an animated GIF, looping forever
+pub fn save_gif(path: &str, frames: &[Frame], palette: &[[u8; 3]]) -> Result<(), GenericError> {
+    let (pixel_width, pixel_height) = frames.first().map_or((0, 0), |frame| frame.get_pixel_size());
+    if pixel_width > u16::MAX as usize || pixel_height > u16::MAX as usize {
+        return Err(GenericError::Validation(format!("Frames of {}x{} pixels are too large for a GIF", pixel_width, pixel_height)));
+    }
+
+    let flat_palette: Vec<u8> = palette.iter().flatten().copied().collect();
+    let to_error = |err: gif::EncodingError| GenericError::new(format!("{}: {}", path, err));
+
+    let mut encoder = gif::Encoder::new(create_file(path)?, pixel_width as u16, pixel_height as u16, &flat_palette)
+        .map_err(to_error)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(to_error)?;
+
+    for frame in frames {
+        let gif_frame = gif::Frame {
+            width: pixel_width as u16,
+            height: pixel_height as u16,
+            delay: GIF_DELAY,
+            buffer: Cow::Owned(frame.get_pixels()),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&gif_frame).map_err(to_error)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frames::{Frame, render_text, save_pngs, save_gif, CELL_SIZE};
+    use crate::utils::get_temp_path;
+    use std::fs;
+
+    const PALETTE: [[u8; 3]; 2] = [[0, 0, 0], [255, 255, 255]];
+
+    fn frames() -> Vec<Frame> {
+        vec![
+            Frame { width: 2, height: 1, cells: vec![0, 1] },
+            Frame { width: 2, height: 1, cells: vec![1, 0] },
+        ]
+    }
+
+    #[test]
+    fn it_scales_cells() {
+        let pixels = frames()[0].get_pixels();
+        assert_eq!(pixels.len(), 2 * CELL_SIZE * CELL_SIZE);
+        assert_eq!(&pixels[..2 * CELL_SIZE], [vec![0; CELL_SIZE], vec![1; CELL_SIZE]].concat().as_slice());
+    }
+
+    #[test]
+    fn it_renders_text() {
+        assert_eq!(render_text(&["#.", ".#"]), "Generation 0\n#.\n\nGeneration 1\n.#\n\n");
+    }
+
+    #[test]
+    fn it_saves_images() {
+        let prefix = get_temp_path("frames");
+        let prefix = prefix.to_str().unwrap();
+
+        let paths = save_pngs(prefix, &frames(), &PALETTE).unwrap();
+        assert_eq!(paths.len(), 2);
+        for path in paths {
+            assert_eq!(&fs::read(&path).unwrap()[1..4], b"PNG");
+            fs::remove_file(path).unwrap();
+        }
+
+        let gif_path = format!("{}.gif", prefix);
+        save_gif(&gif_path, &frames(), &PALETTE).unwrap();
+        assert_eq!(&fs::read(&gif_path).unwrap()[..6], b"GIF89a");
+        fs::remove_file(gif_path).unwrap();
+    }
+}
@@ -0,0 +1,320 @@
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::utils::GenericError;
+
+pub const MOORE_OFFSETS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+pub const VON_NEUMANN_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Cell of a grid, written as a single char in inputs
+pub trait Cell: Sized {
+    /// Whether chars that are not cells are skipped, instead of failing the parsing of their row
+    const SKIPS_UNKNOWN_CHARS: bool = false;
+
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+/// What lies beyond the borders of a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edges {
+    /// Nothing, positions outside the grid have no cell
+    Bounded,
+    /// The grid repeats itself to the left and to the right
+    WrapHorizontally,
+    /// The grid repeats itself in every direction
+    Toroidal,
+}
+
+impl FromStr for Edges {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bounded" => Ok(Edges::Bounded),
+            "wrap" => Ok(Edges::WrapHorizontally),
+            "toroidal" => Ok(Edges::Toroidal),
+            _ => Err(GenericError::Usage(format!("Unknown edges '{}'", s))),
+        }
+    }
+}
+
+/// A line of cells, as found in inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row<T> {
+    cells: Vec<T>,
+}
+
+impl<T: Cell> FromStr for Row<T> {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s.chars()
+            .filter_map(|c| match T::from_char(c) {
+                Some(cell) => Some(Ok(cell)),
+                None if T::SKIPS_UNKNOWN_CHARS => None,
+                None => Some(Err(GenericError::parse(format!("Invalid cell '{}'", c)))),
+            })
+            .collect::<Result<Vec<T>, GenericError>>()?;
+
+        Ok(Row { cells })
+    }
+}
+
+impl<T: Cell> Display for Row<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.cells.iter().try_for_each(|cell| write!(f, "{}", cell.to_char()))
+    }
+}
+
+/// A rectangular grid of cells, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    edges: Edges,
+}
+
+impl<T> Grid<T> {
+    /// Create a bounded grid from its cells, given row by row
+    pub fn new(width: usize, cells: Vec<T>) -> Result<Self, GenericError> {
+        if (width == 0 && !cells.is_empty()) || (width > 0 && !cells.len().is_multiple_of(width)) {
+            return Err(GenericError::Validation(format!("{} cells do not fill rows of {}", cells.len(), width)));
+        }
+
+        Ok(Grid {
+            width,
+            height: cells.len().checked_div(width).unwrap_or(0),
+            cells,
+            edges: Edges::Bounded,
+        })
+    }
+
+    pub fn with_edges(mut self, edges: Edges) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Exchange the cells with others of the same count, such as the next generation of an automaton
+    pub fn swap_cells(&mut self, cells: &mut Vec<T>) {
+        assert_eq!(cells.len(), self.cells.len(), "Swapped cells must fill the grid");
+        std::mem::swap(&mut self.cells, cells);
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(&self.cells[y * self.width + x])
+    }
+
+    /// Position reached from (x, y) by an offset, according to the edges
+    pub fn offset(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let wrap = |value: isize, size: usize, wraps: bool| {
+            if wraps && size > 0 {
+                Some(value.rem_euclid(size as isize) as usize)
+            } else if value >= 0 && (value as usize) < size {
+                Some(value as usize)
+            } else {
+                None
+            }
+        };
+
+        let x = wrap(x as isize + dx, self.width, self.edges != Edges::Bounded)?;
+        let y = wrap(y as isize + dy, self.height, self.edges == Edges::Toroidal)?;
+
+        Some((x, y))
+    }
+
+    /// Cells at the given offsets around (x, y)
+    pub fn neighbours<'a>(&'a self, x: usize, y: usize, offsets: &'a [(isize, isize)]) -> impl Iterator<Item=&'a T> + 'a {
+        offsets.iter()
+            .filter_map(move |offset| self.offset(x, y, *offset))
+            .filter_map(move |(x, y)| self.get(x, y))
+    }
+
+    /// Cells met by moving from (x, y) by steps of `direction`, (x, y) excluded.
+    ///
+    /// The ray stops at the borders of a bounded grid, or when it comes back to (x, y) on a wrapped one.
+    pub fn ray(&self, x: usize, y: usize, direction: (isize, isize)) -> Ray<'_, T> {
+        Ray { grid: self, start: (x, y), position: Some((x, y)), direction }
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item=&T> {
+        self.cells.chunks(self.width.max(1)).nth(y).into_iter().flatten()
+    }
+}
+
+/// Reshaping helpers, as used by the `life` command
+impl<T: Clone> Grid<T> {
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        (0..self.height).filter_map(move |y| self.get(x, y))
+    }
+
+    /// Cells from (x, y) going down and right
+    pub fn diagonal(&self, x: usize, y: usize) -> impl Iterator<Item=&T> {
+        self.get(x, y).into_iter().chain(self.ray(x, y, (1, 1)).map(|(_, cell)| cell))
+    }
+
+    /// Swap rows and columns
+    pub fn transpose(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|x| self.column(x).cloned().collect::<Vec<T>>())
+            .collect();
+
+        Grid { width: self.height, height: self.width, cells, edges: self.edges }
+    }
+
+    /// Rotate by a quarter turn clockwise, `quarter_turns` times
+    pub fn rotate(&self, quarter_turns: usize) -> Self {
+        (0..quarter_turns % 4).fold(self.clone(), |grid, _| {
+            let mut rotated = grid.transpose();
+            for row in rotated.cells.chunks_mut(rotated.width.max(1)) {
+                row.reverse();
+            }
+            rotated
+        })
+    }
+}
+
+impl<T> TryFrom<Vec<Row<T>>> for Grid<T> {
+    type Error = GenericError;
+
+    fn try_from(rows: Vec<Row<T>>) -> Result<Self, Self::Error> {
+        let width = rows.first().map_or(0, |row| row.cells.len());
+        if let Some(index) = rows.iter().position(|row| row.cells.len() != width) {
+            return Err(GenericError::Validation(format!("Row {} has {} cells instead of {}",
+                                                        index + 1, rows[index].cells.len(), width)));
+        }
+
+        Grid::new(width, rows.into_iter().flat_map(|row| row.cells).collect())
+    }
+}
+
+impl<T: Cell> FromStr for Grid<T> {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s.lines()
+            .map(|line| line.parse())
+            .collect::<Result<Vec<Row<T>>, GenericError>>()?;
+
+        Grid::try_from(rows)
+    }
+}
+
+impl<T: Cell> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            self.row(y).try_for_each(|cell| write!(f, "{}", cell.to_char()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterator over the positions and cells of a line of a grid, see `Grid::ray`
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    start: (usize, usize),
+    position: Option<(usize, usize)>,
+    direction: (isize, isize),
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.position?;
+        self.position = self.grid.offset(x, y, self.direction)
+            .filter(|position| *position != self.start);
+
+        let (x, y) = self.position?;
+        self.grid.get(x, y).map(|cell| ((x, y), cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Grid, Row, Cell, Edges, MOORE_OFFSETS, VON_NEUMANN_OFFSETS};
+
+    impl Cell for char {
+        fn from_char(c: char) -> Option<Self> {
+            Some(c)
+        }
+
+        fn to_char(&self) -> char {
+            *self
+        }
+    }
+
+    fn grid() -> Grid<char> {
+        "abc\ndef".parse().unwrap()
+    }
+
+    #[test]
+    fn it_parses_and_displays() {
+        let grid = grid();
+        assert_eq!((grid.get_width(), grid.get_height()), (3, 2));
+        assert_eq!(grid.get(2, 1), Some(&'f'));
+        assert_eq!(grid.get(3, 1), None);
+        assert_eq!(grid.to_string(), "abc\ndef");
+        assert_eq!("abc".parse::<Row<char>>().unwrap().to_string(), "abc");
+
+        assert!("abc\nde".parse::<Grid<char>>().is_err());
+        assert!(Grid::new(2, vec![1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn it_follows_edges() {
+        let bounded = grid();
+        assert_eq!(bounded.offset(0, 0, (-1, 0)), None);
+        assert_eq!(bounded.neighbours(0, 0, &MOORE_OFFSETS).collect::<String>(), "bde");
+        assert_eq!(bounded.neighbours(1, 0, &VON_NEUMANN_OFFSETS).collect::<String>(), "ace");
+
+        let wrapped = grid().with_edges(Edges::WrapHorizontally);
+        assert_eq!(wrapped.offset(0, 0, (-1, 0)), Some((2, 0)));
+        assert_eq!(wrapped.offset(0, 0, (0, -1)), None);
+        assert_eq!(wrapped.ray(0, 0, (2, 1)).map(|(_, cell)| *cell).collect::<String>(), "f");
+
+        let toroidal = grid().with_edges(Edges::Toroidal);
+        assert_eq!(toroidal.offset(0, 0, (-1, -1)), Some((2, 1)));
+        assert_eq!(toroidal.ray(0, 0, (1, 1)).map(|(_, cell)| *cell).collect::<String>(), "ecdbf");
+    }
+
+    #[test]
+    fn it_iterates_lines() {
+        let grid = grid();
+        assert_eq!(grid.row(1).collect::<String>(), "def");
+        assert_eq!(grid.row(2).count(), 0);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.diagonal(0, 0).collect::<String>(), "ae");
+        assert_eq!(grid.ray(2, 1, (-1, 0)).map(|(position, _)| position).collect::<Vec<_>>(), vec![(1, 1), (0, 1)]);
+    }
+
+    #[test]
+    fn it_transposes_and_rotates() {
+        let grid = grid();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate(1).to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate(2).to_string(), "fed\ncba");
+        assert_eq!(grid.rotate(4), grid);
+    }
+}
@@ -1,8 +1,37 @@
 use crate::utils::GenericError;
-use daily_challenge::DailyChallenge;
+use crate::cli::{Command, RunOptions, BenchOptions, VerifyOptions, FramesOptions, GridBenchOptions, ExpensesOptions, PoliciesOptions, CubesOptions, LifeOptions, USAGE};
+use crate::registry::Registry;
+use crate::daily_challenge::{Challenge, DailyChallenge};
+use crate::utils::LoadMode;
+use crate::verify::{Check, Verdict, ExpectedAnswers};
+use crate::frames::FrameFormat;
+use crate::day1::{Day1, Expense};
+use crate::day1::analysis::ExpenseReport;
+use crate::day2::Day2;
+use crate::day2::policy::PolicySet;
+use crate::output::Format;
+use crate::day4::Day4;
+use crate::day4::schema::Schema;
+use crate::day11::{Day11, Neighbourhood};
+use crate::automaton::{Simulation, Automaton, ParallelAutomaton};
+use crate::daily_challenge::Input;
+use crate::grid::Grid;
+use crate::sparse::SparseAutomaton;
+use crate::bench::Stats;
+use crate::utils::XorShift;
+use std::time::Instant;
 
 mod utils;
 mod daily_challenge;
+mod registry;
+mod bench;
+mod output;
+mod verify;
+mod cli;
+mod grid;
+mod automaton;
+mod sparse;
+mod frames;
 
 mod day1;
 mod day2;
@@ -16,21 +45,365 @@ mod day9;
 mod day10;
 mod day11;
 
-fn main() -> Result<(), GenericError> {
-    println!("Hello, AOC 2020 !");
-    println!("=================");
+fn get_challenge(registry: &Registry, day: usize) -> Result<&dyn Challenge, GenericError> {
+    registry.get(day)
+        .ok_or_else(|| GenericError::Usage(format!("Day {} is not available", day)))
+}
+
+/// Day 4 checking passports against the selected schema
+fn get_day4(options: &RunOptions) -> Result<Day4, GenericError> {
+    match &options.schema_path {
+        Some(path) => Ok(Day4::new(Schema::load(path)?)),
+        None => Ok(Day4::default()),
+    }
+}
+
+fn run(registry: &Registry, options: &RunOptions) -> Result<(), GenericError> {
+    let day4 = match options.schema_path {
+        Some(_) => Some(get_day4(options)?),
+        None => None,
+    };
+
+    let mut reports = vec![];
+    for day in &options.days {
+        let challenge = match &day4 {
+            Some(day4) if *day == cli::PASSPORTS_DAY => day4,
+            _ => get_challenge(registry, *day)?,
+        };
+        reports.push(challenge.run(&options.get_input(*day), &options.parts, options.load_mode)?);
+    }
+
+    print!("{}", output::render(&reports, options.format)?);
+
+    Ok(())
+}
+
+fn run_bench(registry: &Registry, options: &BenchOptions) -> Result<(), GenericError> {
+    let mut measures = vec![];
+    for day in &options.run.days {
+        let challenge = get_challenge(registry, *day)?;
+        let input = options.run.get_input(*day).buffered()?;
+        let day_measures = challenge.bench(&input, &options.run.parts, options.iterations)?;
+        for measure in &day_measures {
+            println!("{}", measure);
+        }
+        measures.extend(day_measures);
+    }
+
+    let baseline = bench::load_baseline(&options.baseline_path)?;
+    let regressions = bench::find_regressions(&measures, &baseline, options.threshold);
+    if !regressions.is_empty() {
+        println!();
+        println!("Regressions against {} :", options.baseline_path);
+        for regression in &regressions {
+            println!(" - {}", regression);
+        }
+    }
+
+    if options.save {
+        bench::save_baseline(&options.baseline_path, &measures)?;
+        println!("Baseline saved to {}", options.baseline_path);
+    }
+
+    Ok(())
+}
+
+fn run_verify(registry: &Registry, options: &VerifyOptions) -> Result<(), GenericError> {
+    let answers = match options.run.example {
+        Some(_) => Default::default(),
+        None => verify::load_answers(&options.answers_path)?,
+    };
+
+    let mut checks = vec![];
+    for day in &options.run.days {
+        let challenge = get_challenge(registry, *day)?;
+        let example = options.run.example.and_then(|index| challenge.get_examples().into_iter().nth(index));
+        if options.run.example.is_some() && example.is_none() {
+            println!("day {:>2} : no such example", day);
+            continue;
+        }
+        let get_expected = |part| match example {
+            Some(example) => example.get_expected(part).map(|expected| expected.to_string()),
+            None => answers.get(day).and_then(|expected: &ExpectedAnswers| expected.get(part)),
+        };
+
+        match challenge.run(&options.run.get_input(*day), &options.run.parts, LoadMode::Strict) {
+            Ok(report) => checks.extend(verify::check_report(&report, get_expected)),
+            Err(err) => checks.extend(options.run.parts.iter().map(|part| Check {
+                day: *day,
+                part: *part,
+                verdict: Verdict::Error(err.to_string()),
+            })),
+        }
+    }
+
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let failed = checks.iter()
+        .filter(|check| matches!(check.verdict, Verdict::Fail { .. } | Verdict::Error(_)))
+        .count();
+    let missing = checks.iter()
+        .filter(|check| matches!(check.verdict, Verdict::Missing { .. }))
+        .count();
+    println!();
+    println!("{} passed, {} failed, {} missing", checks.len() - failed - missing, failed, missing);
+
+    if failed > 0 {
+        let reference = match options.run.example {
+            Some(_) => "the examples",
+            None => &options.answers_path,
+        };
+        return Err(GenericError::WrongAnswer(format!("{} answers do not match {}", failed, reference)));
+    }
+
+    Ok(())
+}
+
+/// Render every round of the day 11 seating simulation
+fn run_frames(options: &FramesOptions) -> Result<(), GenericError> {
+    let input = options.run.get_input(cli::FRAMES_DAY).buffered()?;
+    let (grid, _) = Day11.load_data(&input, options.run.load_mode)?;
+    let generations = grid.get_generations(Neighbourhood::from(options.part))?;
+
+    let name = format!("day{}", cli::FRAMES_DAY);
+    let output = options.output.clone().or_else(|| options.format.get_default_output(&name));
+    match options.format {
+        FrameFormat::Text => {
+            let text = frames::render_text(&generations);
+            match output {
+                Some(path) => std::fs::write(&path, text)
+                    .map_err(|err| GenericError::Io { path: Some(path.clone()), source: err })?,
+                None => print!("{}", text),
+            }
+        }
+        FrameFormat::Png => {
+            let frames: Vec<_> = generations.iter().map(|grid| grid.to_frame()).collect();
+            let paths = frames::save_pngs(&output.unwrap_or(name), &frames, &day11::PALETTE)?;
+            println!("{} frames written, up to {}", paths.len(), paths.last().map_or("", |path| path.as_str()));
+        }
+        FrameFormat::Gif => {
+            let frames: Vec<_> = generations.iter().map(|grid| grid.to_frame()).collect();
+            let path = output.unwrap_or(name);
+            frames::save_gif(&path, &frames, &day11::PALETTE)?;
+            println!("{} frames written to {}", frames.len(), path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Compare serial and parallel stepping of random day 11 grids, round after round
+fn run_grid_bench(options: &GridBenchOptions) -> Result<(), GenericError> {
+    let threads = options.threads.unwrap_or_else(automaton::get_available_threads);
+    let mut rng = XorShift::new(2020);
+
+    for size in &options.sizes {
+        let mut serial = day11::Grid::random(*size, *size, &mut rng).to_automaton(Neighbourhood::Adjacent);
+        let mut parallel = ParallelAutomaton::new(serial.clone(), threads);
+        let mut serial_durations = vec![];
+        let mut parallel_durations = vec![];
+        for _ in 0..options.iterations {
+            let start = Instant::now();
+            serial.step();
+            serial_durations.push(start.elapsed());
+
+            let start = Instant::now();
+            parallel.step();
+            parallel_durations.push(start.elapsed());
+
+            if serial.get_grid() != parallel.get_grid() {
+                return Err(GenericError::new(format!("Parallel stepping of a {0}x{0} grid differs from the serial one", size)));
+            }
+        }
+
+        let serial = Stats::from_durations(serial_durations);
+        let parallel = Stats::from_durations(parallel_durations);
+        println!("{0}x{0} serial : median {1:?} | {2} threads : median {3:?} | speedup x{4:.2}",
+                 size, serial.median, threads, parallel.median,
+                 serial.median.as_secs_f64() / parallel.median.as_secs_f64());
+    }
+
+    Ok(())
+}
+
+/// Analyse the day 1 expense report against any target
+fn run_expenses(options: &ExpensesOptions) -> Result<(), GenericError> {
+    let input = options.run.get_input(cli::EXPENSES_DAY).buffered()?;
+    let (expenses, _) = Day1::default().load_data(&input, LoadMode::Strict)?;
+    let report = ExpenseReport::new(expenses);
+    let (k, target) = (options.k, Expense::from(options.target));
+
+    let solutions = report.find_all(k, &target)?;
+    println!("{} sets of {} expenses sum to {}, {} of them with distinct values",
+             report.count_k_subsets(k, &target)?, k, target, solutions.len());
+    for expenses in &solutions {
+        println!(" - {}", day1::describe(expenses));
+    }
+    if solutions.is_empty() {
+        match report.find_closest(k, &target)? {
+            Some(closest) => println!("Closest sum : {} with {}, {} away",
+                                      closest.sum, day1::describe(&closest.expenses), closest.distance),
+            None => println!("Closest sum : none, the report has fewer than {} expenses", k),
+        }
+    }
 
-    println!("{}", day1::Day1::default().solve("src/day1/data.txt")?);
-    println!("{}", day2::Day2::default().solve("src/day2/data.txt")?);
-    println!("{}", day3::Day3::default().solve("src/day3/data.txt")?);
-    println!("{}", day4::Day4::default().solve("src/day4/data.txt")?);
-    println!("{}", day5::Day5::default().solve("src/day5/data.txt")?);
-    println!("{}", day6::Day6::default().solve("src/day6/data.txt")?);
-    println!("{}", day7::Day7::default().solve("src/day7/data.txt")?);
-    println!("{}", day8::Day8::default().solve("src/day8/data.txt")?);
-    println!("{}", day9::Day9::default().solve("src/day9/data.txt")?);
-    println!("{}", day10::Day10::default().solve("src/day10/data.txt")?);
-    println!("{}", day11::Day11::default().solve("src/day11/data.txt")?);
+    match report.find_subset(&target)? {
+        Some(subset) => println!("Any number of expenses : {} ({} expenses)", day1::describe(&subset), subset.len()),
+        None => println!("Any number of expenses : no set sums to {}", target),
+    }
 
     Ok(())
 }
+
+/// Count the day 2 entries violating each policy of a rules file
+fn run_policies(options: &PoliciesOptions) -> Result<(), GenericError> {
+    let policies = PolicySet::load(&options.rules_path)?;
+    let input = options.run.get_input(cli::PASSWORDS_DAY).buffered()?;
+    let (entries, _) = Day2::default().load_data(&input, LoadMode::Strict)?;
+
+    println!("{}", policies.check(&entries));
+
+    Ok(())
+}
+
+/// Report the day 2 entries failing the puzzle policies, with their lines
+fn run_audit(options: &RunOptions) -> Result<(), GenericError> {
+    let (records, warnings) = Day2::default().load_records(&options.get_input(cli::PASSWORDS_DAY), options.load_mode)?;
+    for warning in &warnings {
+        eprintln!("Skipped {}", warning);
+    }
+    let report = day2::audit::audit(records);
+
+    match options.format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report).map_err(|err| GenericError::new(err.to_string()))?),
+        _ => println!("{}", report),
+    }
+
+    Ok(())
+}
+
+/// List the day 4 passports failing their schema, with the outcome of each of their fields
+fn run_passports(options: &RunOptions) -> Result<(), GenericError> {
+    let day = get_day4(options)?;
+    let (records, warnings) = day.load_records(&options.get_input(cli::PASSPORTS_DAY), options.load_mode)?;
+    for warning in &warnings {
+        eprintln!("Skipped {}", warning);
+    }
+    let report = day4::report::diagnose(records, day.get_schema());
+
+    println!("{}", report);
+
+    Ok(())
+}
+
+/// Apply Conway's rules to a 2D slice of cubes lifted to more dimensions
+/// 2D slice of # and . cells read from a path, - for stdin, or the default slice when none
+fn read_slice(input: Option<&str>) -> Result<Grid<bool>, GenericError> {
+    let slice = match input {
+        None => cli::DEFAULT_SLICE.to_string(),
+        Some("-") => match Input::Stdin.buffered()? {
+            Input::Text(text) => text,
+            _ => unreachable!("stdin is buffered as text"),
+        },
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| GenericError::Io { path: Some(path.to_string()), source: err })?,
+    };
+
+    slice.trim_end().parse()
+}
+
+fn run_cubes(options: &CubesOptions) -> Result<(), GenericError> {
+    let grid = read_slice(options.input.as_deref())?;
+
+    match options.dimensions {
+        2 => print_cycles(SparseAutomaton::<_, 2>::from_grid(&grid, |cell| *cell, sparse::conway), options.cycles),
+        3 => print_cycles(SparseAutomaton::<_, 3>::from_grid(&grid, |cell| *cell, sparse::conway), options.cycles),
+        _ => print_cycles(SparseAutomaton::<_, 4>::from_grid(&grid, |cell| *cell, sparse::conway), options.cycles),
+    }
+
+    Ok(())
+}
+
+fn print_cycles<R: sparse::CountRule, const N: usize>(mut automaton: SparseAutomaton<R, N>, cycles: usize) {
+    for cycle in 1..=cycles {
+        automaton.step();
+        match automaton.get_bounds() {
+            Some(bounds) => println!("cycle {} : {} active cubes from {:?} to {:?}",
+                                     cycle, automaton.count_active(), bounds.min, bounds.max),
+            None => println!("cycle {} : no active cube", cycle),
+        }
+    }
+}
+
+/// Apply Conway's rules to a bounded or wrapped 2D slice, over any neighbourhood
+fn run_life(options: &LifeOptions) -> Result<(), GenericError> {
+    let mut grid = read_slice(options.input.as_deref())?.with_edges(options.edges);
+    if options.transpose {
+        grid = grid.transpose();
+    }
+    let grid = grid.rotate(options.quarter_turns);
+    let conway = |cell: &bool, neighbours: &[&bool]| sparse::conway(*cell, neighbours.iter().filter(|neighbour| ***neighbour).count());
+    let mut automaton = Automaton::new(grid, options.neighbourhood.parse()?, conway);
+
+    for generation in 1..=options.generations {
+        automaton.step();
+        println!("generation {} : {}\n{}", generation, describe_slice(automaton.get_grid()), automaton.get_grid());
+    }
+
+    Ok(())
+}
+
+/// Count and extent of the active cells of a 2D slice
+fn describe_slice(grid: &Grid<bool>) -> String {
+    let is_active = |cell: &&bool| **cell;
+    let rows: Vec<usize> = (0..grid.get_height()).filter(|y| grid.row(*y).any(|cell| *cell)).collect();
+    let columns: Vec<usize> = (0..grid.get_width()).filter(|x| grid.column(*x).any(|cell| *cell)).collect();
+
+    match (rows.first(), rows.last(), columns.first(), columns.last()) {
+        (Some(top), Some(bottom), Some(left), Some(right)) => format!("{} active cells in rows {}-{} and columns {}-{}, {} on the main diagonal",
+                                                                      grid.get_cells().iter().filter(is_active).count(), top, bottom, left, right,
+                                                                      grid.diagonal(0, 0).filter(is_active).count()),
+        _ => "no active cell".to_string(),
+    }
+}
+
+/// Process exit code for each kind of failure
+fn get_exit_code(err: &GenericError) -> i32 {
+    match err {
+        _ if err.is_input_missing() => 2,
+        GenericError::NoSolution(_) => 3,
+        GenericError::WrongAnswer(_) => 4,
+        GenericError::Usage(_) => 64,
+        _ => 1,
+    }
+}
+
+fn main() {
+    let registry = Registry::default();
+
+    let result = Command::from_args(std::env::args().skip(1), &registry.days())
+        .and_then(|command| match command {
+            Command::Run(options) => run(&registry, &options),
+            Command::Bench(options) => run_bench(&registry, &options),
+            Command::Verify(options) => run_verify(&registry, &options),
+            Command::Frames(options) => run_frames(&options),
+            Command::BenchGrid(options) => run_grid_bench(&options),
+            Command::Expenses(options) => run_expenses(&options),
+            Command::Policies(options) => run_policies(&options),
+            Command::Audit(options) => run_audit(&options),
+            Command::Passports(options) => run_passports(&options),
+            Command::Cubes(options) => run_cubes(&options),
+            Command::Life(options) => run_life(&options),
+            Command::Help => {
+                println!("{}", USAGE);
+                Ok(())
+            }
+        });
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(get_exit_code(&err));
+    }
+}
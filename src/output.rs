@@ -0,0 +1,147 @@
+use std::str::FromStr;
+use serde::Serialize;
+use crate::daily_challenge::DayReport;
+use crate::utils::GenericError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = GenericError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(GenericError::Usage(format!("Unknown format '{}'", s))),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDay<'a> {
+    day: usize,
+    parse_ns: u64,
+    warnings: &'a [String],
+    parts: Vec<JsonPart<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonPart<'a> {
+    part: usize,
+    answer: &'a str,
+    explanation: Option<&'a str>,
+    duration_ns: u64,
+}
+
+impl<'a> From<&'a DayReport> for JsonDay<'a> {
+    fn from(report: &'a DayReport) -> Self {
+        JsonDay {
+            day: report.day,
+            parse_ns: report.parse_duration.as_nanos() as u64,
+            warnings: &report.warnings,
+            parts: report.parts.iter()
+                .map(|part| JsonPart {
+                    part: part.part.get_num(),
+                    answer: &part.answer.value,
+                    explanation: part.answer.explanation.as_deref(),
+                    duration_ns: part.duration.as_nanos() as u64,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Quote a CSV field when it holds a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn render(reports: &[DayReport], format: Format) -> Result<String, GenericError> {
+    match format {
+        Format::Text => {
+            let mut result = String::new();
+            result.push_str("Hello, AOC 2020 !\n");
+            result.push_str("=================\n");
+            for report in reports {
+                result.push_str(&report.to_string());
+                result.push('\n');
+            }
+            Ok(result)
+        }
+        Format::Json => {
+            let days: Vec<JsonDay> = reports.iter().map(|report| report.into()).collect();
+            let json = serde_json::to_string_pretty(&days)
+                .map_err(|err| GenericError::new(err.to_string()))?;
+            Ok(json + "\n")
+        }
+        Format::Csv => {
+            let mut result = String::from("day,part,answer,explanation,duration_ns\n");
+            for report in reports {
+                for part in &report.parts {
+                    result.push_str(&format!("{},{},{},{},{}\n",
+                                             report.day,
+                                             part.part,
+                                             csv_field(&part.answer.value),
+                                             csv_field(part.answer.explanation.as_deref().unwrap_or("")),
+                                             part.duration.as_nanos()));
+                }
+            }
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::output::{render, Format};
+    use crate::daily_challenge::{DayReport, PartReport, Part, Answer};
+    use std::time::Duration;
+
+    fn reports() -> Vec<DayReport> {
+        vec![DayReport {
+            day: 1,
+            warnings: vec![],
+            parse_duration: Duration::from_nanos(1500),
+            parts: vec![
+                PartReport {
+                    part: Part::One,
+                    answer: Answer::new(514579).with_explanation("product of 1721 and 299".to_string()),
+                    duration: Duration::from_nanos(200),
+                },
+                PartReport {
+                    part: Part::Two,
+                    answer: Answer::new("a, \"b\""),
+                    duration: Duration::from_nanos(300),
+                },
+            ],
+        }]
+    }
+
+    #[test]
+    fn it_renders_csv() {
+        assert_eq!(render(&reports(), Format::Csv).unwrap(), "day,part,answer,explanation,duration_ns\n\
+            1,1,514579,product of 1721 and 299,200\n\
+            1,2,\"a, \"\"b\"\"\",,300\n");
+    }
+
+    #[test]
+    fn it_renders_json() {
+        let json: serde_json::Value = serde_json::from_str(&render(&reports(), Format::Json).unwrap()).unwrap();
+        assert_eq!(json[0]["day"], 1);
+        assert_eq!(json[0]["parse_ns"], 1500);
+        assert_eq!(json[0]["parts"][0]["part"], 1);
+        assert_eq!(json[0]["parts"][0]["answer"], "514579");
+        assert_eq!(json[0]["parts"][0]["explanation"], "product of 1721 and 299");
+        assert_eq!(json[0]["parts"][1]["explanation"], serde_json::Value::Null);
+    }
+}
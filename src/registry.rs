@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use crate::daily_challenge::Challenge;
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11};
+
+/// All known challenges, indexed by their day number
+pub struct Registry {
+    challenges: BTreeMap<usize, Box<dyn Challenge>>
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry { challenges: BTreeMap::default() }
+    }
+
+    /// Register a challenge under its own day number, replacing any previous one
+    pub fn register(&mut self, challenge: Box<dyn Challenge>) -> &mut Self {
+        self.challenges.insert(challenge.get_day_num(), challenge);
+        self
+    }
+
+    pub fn get(&self, day: usize) -> Option<&dyn Challenge> {
+        self.challenges.get(&day).map(|challenge| challenge.as_ref())
+    }
+
+    pub fn days(&self) -> Vec<usize> {
+        self.iter().map(|challenge| challenge.get_day_num()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&dyn Challenge> {
+        self.challenges.values().map(|challenge| challenge.as_ref())
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry::new();
+        registry
+            .register(Box::new(day1::Day1::default()))
+            .register(Box::new(day2::Day2::default()))
+            .register(Box::new(day3::Day3::default()))
+            .register(Box::new(day4::Day4::default()))
+            .register(Box::new(day5::Day5::default()))
+            .register(Box::new(day6::Day6::default()))
+            .register(Box::new(day7::Day7::default()))
+            .register(Box::new(day8::Day8::default()))
+            .register(Box::new(day9::Day9::default()))
+            .register(Box::new(day10::Day10))
+            .register(Box::new(day11::Day11));
+
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::Registry;
+    use crate::daily_challenge::{Input, Part};
+    use crate::utils::LoadMode;
+
+    #[test]
+    fn it_registers_every_day() {
+        let registry = Registry::default();
+
+        assert_eq!(registry.days(), (1..=11).collect::<Vec<usize>>());
+        assert_eq!(registry.get(7).map(|challenge| challenge.get_day_num()), Some(7));
+        assert!(registry.get(12).is_none());
+        assert!(registry.iter().enumerate().all(|(i, challenge)| challenge.get_day_num() == i + 1));
+    }
+
+    #[test]
+    fn it_solves_every_example() {
+        let registry = Registry::default();
+
+        for challenge in registry.iter() {
+            assert!(!challenge.get_examples().is_empty(), "day {} has no example", challenge.get_day_num());
+            for (index, example) in challenge.get_examples().into_iter().enumerate() {
+                let parts: Vec<Part> = vec![Part::One, Part::Two].into_iter()
+                    .filter(|part| example.get_expected(*part).is_some())
+                    .collect();
+                let report = challenge.run(&Input::Example(index), &parts, LoadMode::Strict).unwrap();
+
+                for part_report in report.parts {
+                    assert_eq!(
+                        Some(part_report.answer.value.as_str()),
+                        example.get_expected(part_report.part),
+                        "day {} example {} part {}", report.day, index + 1, part_report.part
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Unbounded automata with two-state cells, storing only the active ones.
+//!
+//! The `cubes` command runs them from a 2D slice lifted to 2, 3 or 4 dimensions.
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use crate::automaton::Simulation;
+use crate::grid::{Grid, Cell};
+
+/// Coordinates of a cell in `N` dimensions
+pub type Coordinates<const N: usize> = [i64; N];
+
+/// Offsets of the 3^N - 1 cells touching a cell, diagonals included
+pub fn moore_offsets<const N: usize>() -> Vec<Coordinates<N>> {
+    let mut offsets = vec![[0; N]];
+    for axis in 0..N {
+        offsets = offsets.into_iter()
+            .flat_map(|offset| (-1..=1).map(move |delta| {
+                let mut offset = offset;
+                offset[axis] = delta;
+                offset
+            }))
+            .collect();
+    }
+    offsets.retain(|offset| offset.iter().any(|delta| *delta != 0));
+
+    offsets
+}
+
+/// Transition of a cell, given whether it is active and how many of its neighbours are.
+///
+/// An inactive cell without active neighbours must stay inactive, as such cells are never visited.
+pub trait CountRule {
+    fn apply(&self, active: bool, active_neighbours: usize) -> bool;
+}
+
+impl<F> CountRule for F
+    where F: Fn(bool, usize) -> bool
+{
+    fn apply(&self, active: bool, active_neighbours: usize) -> bool {
+        self(active, active_neighbours)
+    }
+}
+
+/// Conway's rule: active cells stay active with 2 or 3 active neighbours, inactive ones need 3
+pub fn conway(active: bool, active_neighbours: usize) -> bool {
+    active_neighbours == 3 || (active && active_neighbours == 2)
+}
+
+/// Cell of a 2D slice, '#' when active and '.' when inactive
+impl Cell for bool {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        if *self { '#' } else { '.' }
+    }
+}
+
+/// Smallest and largest coordinates of the active cells, on each axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds<const N: usize> {
+    pub min: Coordinates<N>,
+    pub max: Coordinates<N>,
+}
+
+impl<const N: usize> Bounds<N> {
+    fn extend(bounds: Option<Self>, coordinates: &Coordinates<N>) -> Option<Self> {
+        let mut bounds = bounds.unwrap_or(Bounds { min: *coordinates, max: *coordinates });
+        for (axis, value) in coordinates.iter().enumerate() {
+            bounds.min[axis] = bounds.min[axis].min(*value);
+            bounds.max[axis] = bounds.max[axis].max(*value);
+        }
+
+        Some(bounds)
+    }
+}
+
+/// An unbounded automaton in `N` dimensions, as the set of its active cells
+#[derive(Debug, Clone)]
+pub struct SparseAutomaton<R, const N: usize> {
+    active: HashSet<Coordinates<N>>,
+    buffer: HashSet<Coordinates<N>>,
+    counts: HashMap<Coordinates<N>, usize>,
+    offsets: Vec<Coordinates<N>>,
+    bounds: Option<Bounds<N>>,
+    rule: R,
+}
+
+impl<R: CountRule, const N: usize> SparseAutomaton<R, N> {
+    /// Create an automaton over the Moore neighbourhood
+    pub fn new<I: IntoIterator<Item=Coordinates<N>>>(active: I, rule: R) -> Self {
+        let active: HashSet<Coordinates<N>> = active.into_iter().collect();
+        let bounds = active.iter().fold(None, Bounds::extend);
+
+        SparseAutomaton {
+            active,
+            buffer: HashSet::new(),
+            counts: HashMap::new(),
+            offsets: moore_offsets(),
+            bounds,
+            rule,
+        }
+    }
+
+    /// Lift a 2D grid to the plane of `N` dimensions where the other coordinates are 0
+    pub fn from_grid<T>(grid: &Grid<T>, is_active: fn(&T) -> bool, rule: R) -> Self {
+        const { assert!(N >= 2, "A grid needs at least two dimensions") };
+
+        let active = grid.get_cells().iter()
+            .enumerate()
+            .filter(|(_, cell)| is_active(cell))
+            .map(|(index, _)| {
+                let mut coordinates = [0; N];
+                coordinates[0] = (index % grid.get_width()) as i64;
+                coordinates[1] = (index / grid.get_width()) as i64;
+                coordinates
+            });
+
+        SparseAutomaton::new(active, rule)
+    }
+
+    pub fn count_active(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Bounds of the active cells, none when every cell is inactive
+    pub fn get_bounds(&self) -> Option<Bounds<N>> {
+        self.bounds
+    }
+}
+
+impl<R: CountRule, const N: usize> Simulation for SparseAutomaton<R, N> {
+    type State = Vec<Coordinates<N>>;
+
+    fn step(&mut self) -> bool {
+        self.counts.clear();
+        for coordinates in &self.active {
+            for offset in &self.offsets {
+                let mut neighbour = *coordinates;
+                for axis in 0..N {
+                    neighbour[axis] += offset[axis];
+                }
+                *self.counts.entry(neighbour).or_insert(0) += 1;
+            }
+        }
+
+        let mut next = mem::take(&mut self.buffer);
+        next.clear();
+        let mut bounds = None;
+        let candidates = self.counts.iter()
+            .map(|(coordinates, count)| (coordinates, *count))
+            .chain(self.active.iter()
+                .filter(|coordinates| !self.counts.contains_key(*coordinates))
+                .map(|coordinates| (coordinates, 0)));
+        for (coordinates, count) in candidates {
+            if self.rule.apply(self.active.contains(coordinates), count) {
+                bounds = Bounds::extend(bounds, coordinates);
+                next.insert(*coordinates);
+            }
+        }
+
+        let changed = next != self.active;
+        self.buffer = mem::replace(&mut self.active, next);
+        self.bounds = bounds;
+
+        changed
+    }
+
+    /// The active cells, sorted so that equal sets give equal states
+    fn get_state(&self) -> Self::State {
+        let mut active: Vec<Coordinates<N>> = self.active.iter().copied().collect();
+        active.sort_unstable();
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sparse::{SparseAutomaton, Bounds, moore_offsets, conway};
+    use crate::automaton::{Simulation, SteadyState};
+    use crate::grid::Grid;
+
+    fn cubes<const N: usize>(cycles: usize) -> usize {
+        let grid: Grid<bool> = ".#.\n..#\n###".parse().unwrap();
+        let mut automaton = SparseAutomaton::<_, N>::from_grid(&grid, |cell| *cell, conway);
+        for _ in 0..cycles {
+            automaton.step();
+        }
+
+        automaton.count_active()
+    }
+
+    #[test]
+    fn it_builds_neighbourhoods() {
+        assert_eq!(moore_offsets::<2>().len(), 8);
+        assert_eq!(moore_offsets::<3>().len(), 26);
+        assert_eq!(moore_offsets::<4>().len(), 80);
+    }
+
+    #[test]
+    fn it_grows_in_every_dimension() {
+        assert_eq!(cubes::<2>(1), 5);
+        assert_eq!(cubes::<3>(1), 11);
+        assert_eq!(cubes::<3>(6), 112);
+        assert_eq!(cubes::<4>(6), 848);
+    }
+
+    #[test]
+    fn it_tracks_bounds() {
+        let mut glider = SparseAutomaton::new(vec![[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]], conway);
+        assert_eq!(glider.get_bounds(), Some(Bounds { min: [0, 0], max: [2, 2] }));
+
+        for _ in 0..4 {
+            assert!(glider.step());
+        }
+        assert_eq!(glider.get_bounds(), Some(Bounds { min: [1, 1], max: [3, 3] }));
+        assert!(glider.get_state().contains(&[2, 1]));
+
+        let mut lonely = SparseAutomaton::new(vec![[0, 0, 0]], conway);
+        assert!(lonely.step());
+        assert_eq!(lonely.get_bounds(), None);
+    }
+
+    #[test]
+    fn it_detects_steady_states() {
+        let mut blinker = SparseAutomaton::new(vec![[0, -1], [0, 0], [0, 1]], conway);
+        assert_eq!(blinker.run_until_steady(10).unwrap(), SteadyState { start: 0, period: 2 });
+
+        let mut glider = SparseAutomaton::new(vec![[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]], conway);
+        assert!(glider.run_until_steady(20).is_err());
+    }
+}
@@ -4,52 +4,179 @@ use std::str::FromStr;
 use std::fmt::{Debug, Display, Formatter};
 use std::error::Error;
 use std::num::ParseIntError;
+use std::convert::Infallible;
 use crate::day8::ExitCode;
 
 
-pub fn load_data<T: FromStr>(file_name: &str, split_pattern: &str) -> Result<Vec<T>, LoadError>
-    where T::Err: Error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    /// Fail on the first record that cannot be parsed
+    Strict,
+    /// Skip records that cannot be parsed, reporting them as warnings
+    Lenient,
+}
+
+/// Records of an input, each one with its position
+pub type Records<T> = Vec<(T, Position)>;
+
+/// Load and parse every record of a file, see `parse_data`
+pub fn load_data<T: FromStr>(file_name: &str, split_pattern: &str, mode: LoadMode) -> Result<(Records<T>, Vec<LoadError>), LoadError>
+    where T::Err: Error + 'static
+{
+    let file = File::open(file_name).map_err(|err| LoadError::io(file_name, err))?;
+    read_data(file, file_name, split_pattern, mode)
+}
+
+/// Read and parse every record of any source, such as stdin, see `parse_data`
+pub fn read_data<T: FromStr, R: Read>(mut reader: R, source_name: &str, split_pattern: &str, mode: LoadMode) -> Result<(Records<T>, Vec<LoadError>), LoadError>
+    where T::Err: Error + 'static
 {
-    let mut file = File::open(file_name)?;
     let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    reader.read_to_string(&mut contents).map_err(|err| LoadError::io(source_name, err))?;
+
+    parse_data(&contents, source_name, split_pattern, mode)
+}
+
+/// Parse every record of an input with its position, the blank records ending it (such as the one
+/// after the trailing newline) are ignored. `source_name` locates errors, as a file name does.
+///
+/// A blank record followed by other records is invalid. In lenient mode, the returned
+/// warnings list every record that was skipped.
+pub fn parse_data<T: FromStr>(contents: &str, source_name: &str, split_pattern: &str, mode: LoadMode) -> Result<(Records<T>, Vec<LoadError>), LoadError>
+    where T::Err: Error + 'static
+{
+    let mut result = vec![];
+    let mut warnings = vec![];
+    let mut line = 1;
+    let contents = contents.strip_suffix('\n').unwrap_or(contents);
+    let records: Vec<&str> = contents.split(split_pattern).collect();
+    let end = records.iter().rposition(|raw| !raw.trim().is_empty()).map_or(0, |last| last + 1);
+    for (index, raw) in records.into_iter().take(end).enumerate() {
+        let record_line = line;
+        line += raw.matches('\n').count() + split_pattern.matches('\n').count();
 
-    let result = contents.split(split_pattern).into_iter()
-        .map(|item| item.parse::<T>())
-        .filter(|item| item.is_ok())
-        .collect::<Result<Vec<T>, T::Err>>()?;
+        let parsed: Result<T, Box<dyn Error>> = match raw.trim().is_empty() {
+            true => Err(GenericError::parse("Blank record".to_string()).into()),
+            false => raw.parse().map_err(|err: T::Err| err.into()),
+        };
+        match parsed {
+            Ok(item) => result.push((item, Position { index: index + 1, line: record_line })),
+            Err(source) => {
+                let error = LoadError::Record {
+                    location: Location {
+                        file: source_name.to_string(),
+                        index: index + 1,
+                        line: record_line,
+                        raw: raw.to_string(),
+                    },
+                    source,
+                };
+                match mode {
+                    LoadMode::Strict => return Err(error),
+                    LoadMode::Lenient => warnings.push(error),
+                }
+            }
+        }
+    }
+
+    Ok((result, warnings))
+}
+
+/// Position of a parsed record in its input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// 1-based index of the record in the input
+    pub index: usize,
+    /// 1-based line where the record starts
+    pub line: usize,
+}
+
+/// Position of a record in an input file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    /// 1-based index of the record in the file
+    pub index: usize,
+    /// 1-based line where the record starts
+    pub line: usize,
+    pub raw: String,
+}
 
-    Ok(result)
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: record {} '{}'", self.file, self.line, self.index, self.raw)
+    }
 }
 
 #[derive(Debug)]
-pub struct GenericError {
-    message: String
+pub enum GenericError {
+    /// The input could not be read
+    Io {
+        path: Option<String>,
+        source: std::io::Error,
+    },
+    /// Some input could not be parsed, located in its file when known
+    Parse {
+        message: String,
+        location: Option<Location>,
+        source: Option<Box<dyn Error>>,
+    },
+    /// The command line is invalid
+    Usage(String),
+    /// The input was parsed but does not respect the puzzle rules
+    Validation(String),
+    /// The puzzle has no solution for this input
+    NoSolution(String),
+    /// An answer differs from the expected one
+    WrongAnswer(String),
+    /// An arithmetic operation exceeds the range of its integer type
+    Overflow(String),
+    /// Any other failure specific to a puzzle
+    Puzzle(String),
 }
 
 impl GenericError {
     pub fn new(message: String) -> Self {
-        GenericError { message }
+        GenericError::Puzzle(message)
     }
-    pub fn throw<O>(message: &str) -> Result<O, Self> {
-        Err(GenericError { message: message.to_string() })
+    pub fn parse(message: String) -> Self {
+        GenericError::Parse { message, location: None, source: None }
+    }
+
+    /// Whether the error comes from an input file that does not exist
+    pub fn is_input_missing(&self) -> bool {
+        matches!(self, GenericError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound)
     }
 }
 
 impl From<LoadError> for GenericError
 {
     fn from(err: LoadError) -> Self {
-        GenericError {
-            message: err.to_string()
+        match err {
+            LoadError::Io { file, source } => GenericError::Io { path: Some(file), source },
+            LoadError::Record { location, source } => GenericError::Parse {
+                message: source.to_string(),
+                location: Some(location),
+                source: Some(source),
+            },
         }
     }
 }
 
+impl From<Infallible> for GenericError
+{
+    fn from(err: Infallible) -> Self {
+        match err {}
+    }
+}
+
 impl From<ParseIntError> for GenericError
 {
     fn from(err: ParseIntError) -> Self {
-        GenericError {
-            message: err.to_string()
+        GenericError::Parse {
+            message: err.to_string(),
+            location: None,
+            source: Some(Box::new(err)),
         }
     }
 }
@@ -58,8 +185,10 @@ impl From<ParseIntError> for GenericError
 impl From<regex::Error> for GenericError
 {
     fn from(err: regex::Error) -> Self {
-        GenericError {
-            message: err.to_string()
+        GenericError::Parse {
+            message: err.to_string(),
+            location: None,
+            source: Some(Box::new(err)),
         }
     }
 }
@@ -77,36 +206,210 @@ impl From<ExitCode> for GenericError
 impl From<std::io::Error> for GenericError
 {
     fn from(err: std::io::Error) -> Self {
-        GenericError {
-            message: err.to_string()
-        }
+        GenericError::Io { path: None, source: err }
     }
 }
 
 impl Display for GenericError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
+        match self {
+            GenericError::Io { path: Some(path), source } => write!(f, "{}: {}", path, source),
+            GenericError::Io { path: None, source } => write!(f, "{}", source),
+            GenericError::Parse { message, location: Some(location), .. } => write!(f, "{} is invalid: {}", location, message),
+            GenericError::Parse { message, location: None, .. } => write!(f, "{}", message),
+            GenericError::Usage(message) => write!(f, "{}", message),
+            GenericError::Validation(message) => write!(f, "{}", message),
+            GenericError::NoSolution(message) => write!(f, "{}", message),
+            GenericError::WrongAnswer(message) => write!(f, "{}", message),
+            GenericError::Overflow(message) => write!(f, "{}", message),
+            GenericError::Puzzle(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for GenericError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GenericError::Io { source, .. } => Some(source),
+            GenericError::Parse { source: Some(source), .. } => Some(source.as_ref()),
+            _ => None,
+        }
     }
 }
 
-impl Error for GenericError {}
+#[derive(Debug)]
+pub enum LoadError {
+    Io {
+        file: String,
+        source: std::io::Error,
+    },
+    Record {
+        location: Location,
+        source: Box<dyn Error>,
+    },
+}
 
-pub struct LoadError {
-    message: String
+impl LoadError {
+    pub fn io(file: &str, source: std::io::Error) -> Self {
+        LoadError::Io {
+            file: file.to_string(),
+            source,
+        }
+    }
 }
 
-impl<T> From<T> for LoadError
-    where T: Error
-{
-    fn from(err: T) -> Self {
-        LoadError {
-            message: err.to_string()
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io { file, source } => write!(f, "{}: {}", file, source),
+            LoadError::Record { location, source } => write!(f, "{} is invalid: {}", location, source),
         }
     }
 }
 
-impl ToString for LoadError {
-    fn to_string(&self) -> String {
-        self.message.clone()
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io { source, .. } => Some(source),
+            LoadError::Record { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+/// Path in the temporary directory, unique to the running process so that concurrent test runs do not collide
+#[cfg(test)]
+pub fn get_temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("aoc2020_{}_{}", std::process::id(), name))
+}
+
+/// Small deterministic pseudo-random generator (xorshift64), to build synthetic inputs
+#[derive(Debug, Clone)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        XorShift { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Number in 0..bound
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{load_data, read_data, parse_data, get_temp_path, LoadMode, LoadError, GenericError, XorShift, Records};
+    use std::error::Error;
+    use std::fs;
+
+    fn items<T>(records: Records<T>) -> Vec<T> {
+        records.into_iter().map(|(item, _)| item).collect()
+    }
+
+    #[test]
+    fn it_reports_invalid_records() {
+        let file_path = get_temp_path("utils_load_data.txt");
+        let file_name = file_path.to_str().unwrap();
+        fs::write(&file_path, "12\n34\nx5\n67\n\n").unwrap();
+
+        let strict = load_data::<usize>(file_name, "\n", LoadMode::Strict);
+        match strict {
+            Err(LoadError::Record { location, .. }) => {
+                assert_eq!(location.index, 3);
+                assert_eq!(location.line, 3);
+                assert_eq!(location.raw, "x5");
+            }
+            _ => panic!("Expected a record error"),
+        }
+
+        let (records, warnings) = load_data::<usize>(file_name, "\n", LoadMode::Lenient).unwrap();
+        assert_eq!(records.iter().map(|(_, position)| position.line).collect::<Vec<usize>>(), vec![1, 2, 4]);
+        assert_eq!(items(records), vec![12, 34, 67]);
+        assert_eq!(warnings.len(), 1);
+
+        fs::write(&file_path, "1\n\n2\n\n3\nx\n\n4\n").unwrap();
+        match load_data::<usize>(file_name, "\n\n", LoadMode::Strict) {
+            Err(LoadError::Record { location, .. }) => {
+                assert_eq!(location.index, 3);
+                assert_eq!(location.line, 5);
+            }
+            _ => panic!("Expected a record error"),
+        }
+
+        fs::remove_file(&file_path).unwrap();
+        assert!(matches!(load_data::<usize>(file_name, "\n", LoadMode::Strict), Err(LoadError::Io { .. })));
+    }
+
+    #[test]
+    fn it_reads_any_source() {
+        let (records, _) = read_data::<usize, _>("1\n\n2\n".as_bytes(), "bytes", "\n\n", LoadMode::Strict).unwrap();
+        assert_eq!((records[1].1.index, records[1].1.line), (2, 3));
+        assert_eq!(items(records), vec![1, 2]);
+
+        match read_data::<usize, _>(std::io::Cursor::new("1\ntwo\n"), "cursor", "\n", LoadMode::Strict) {
+            Err(LoadError::Record { location, .. }) => {
+                assert_eq!(location.file, "cursor");
+                assert_eq!(location.line, 2);
+            }
+            _ => panic!("Expected a record error"),
+        }
+    }
+
+    #[test]
+    fn it_only_ignores_trailing_blank_records() {
+        let (records, _) = parse_data::<usize>("1\n2\n\n\n", "text", "\n", LoadMode::Strict).unwrap();
+        assert_eq!(items(records), vec![1, 2]);
+        let (records, _) = parse_data::<usize>("", "text", "\n", LoadMode::Strict).unwrap();
+        assert!(records.is_empty());
+
+        match parse_data::<usize>("1\n \n2\n", "text", "\n", LoadMode::Strict) {
+            Err(LoadError::Record { location, source }) => {
+                assert_eq!((location.index, location.line), (2, 2));
+                assert_eq!(source.to_string(), "Blank record");
+            }
+            _ => panic!("Expected a record error"),
+        }
+
+        let (records, warnings) = parse_data::<usize>("1\n\n\n\n2\n", "text", "\n\n", LoadMode::Lenient).unwrap();
+        assert_eq!(items(records), vec![1, 2]);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn it_keeps_error_sources() {
+        let parse_error: GenericError = "x".parse::<usize>().unwrap_err().into();
+        assert!(matches!(parse_error, GenericError::Parse { location: None, .. }));
+        assert!(parse_error.source().unwrap().is::<std::num::ParseIntError>());
+
+        let missing_file = get_temp_path("utils_missing_file.txt");
+        let load_error: GenericError = load_data::<usize>(missing_file.to_str().unwrap(), "\n", LoadMode::Strict)
+            .unwrap_err()
+            .into();
+        assert!(load_error.is_input_missing());
+        assert!(load_error.source().unwrap().is::<std::io::Error>());
+
+        assert!(GenericError::NoSolution("none".to_string()).source().is_none());
+    }
+
+    #[test]
+    fn it_generates_pseudo_random_numbers() {
+        let mut rng = XorShift::new(42);
+        let numbers: Vec<u64> = (0..100).map(|_| rng.next_below(10)).collect();
+        assert!(numbers.iter().all(|number| *number < 10));
+        assert!((0..10).all(|digit| numbers.contains(&digit)));
+
+        let mut same_seed = XorShift::new(42);
+        assert_eq!(same_seed.next_below(10), numbers[0]);
+        assert_ne!(XorShift::new(0).next_u64(), 0);
     }
 }
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use serde::Deserialize;
+use crate::daily_challenge::{DayReport, Part};
+use crate::utils::GenericError;
+
+/// Expected answers of a day, as found in the answers file
+#[derive(Debug, Default, Deserialize, PartialEq)]
+pub struct ExpectedAnswers {
+    part1: Option<toml::Value>,
+    part2: Option<toml::Value>,
+}
+
+impl ExpectedAnswers {
+    pub fn get(&self, part: Part) -> Option<String> {
+        let value = match part {
+            Part::One => self.part1.as_ref(),
+            Part::Two => self.part2.as_ref(),
+        };
+
+        value.map(|value| match value {
+            toml::Value::String(string) => string.clone(),
+            other => other.to_string(),
+        })
+    }
+}
+
+/// Parse an answers file made of `[dayN]` tables holding `part1` and `part2` keys
+pub fn parse_answers(contents: &str) -> Result<BTreeMap<usize, ExpectedAnswers>, GenericError> {
+    let tables: BTreeMap<String, ExpectedAnswers> = toml::from_str(contents)
+        .map_err(|err| GenericError::parse(err.to_string()))?;
+
+    tables.into_iter()
+        .map(|(name, answers)| {
+            let day = name.strip_prefix("day")
+                .and_then(|day| day.parse().ok())
+                .ok_or_else(|| GenericError::parse(format!("Invalid day table '{}'", name)))?;
+            Ok((day, answers))
+        })
+        .collect()
+}
+
+pub fn load_answers(file_path: &str) -> Result<BTreeMap<usize, ExpectedAnswers>, GenericError> {
+    let contents = fs::read_to_string(file_path)
+        .map_err(|err| GenericError::Io { path: Some(file_path.to_string()), source: err })?;
+
+    parse_answers(&contents)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    /// No expected answer is known for this part
+    Missing { actual: String },
+    /// The part could not be solved
+    Error(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub day: usize,
+    pub part: Part,
+    pub verdict: Verdict,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {:>2} part {} : ", self.day, self.part)?;
+        match &self.verdict {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected, actual } => write!(f, "FAIL expected {}, got {}", expected, actual),
+            Verdict::Missing { actual } => write!(f, "missing, got {}", actual),
+            Verdict::Error(message) => write!(f, "ERROR {}", message),
+        }
+    }
+}
+
+/// Compare the answers of a day with the expected ones
+pub fn check_report<F>(report: &DayReport, get_expected: F) -> Vec<Check>
+    where F: Fn(Part) -> Option<String>
+{
+    report.parts.iter()
+        .map(|part_report| {
+            let actual = part_report.answer.value.clone();
+            let verdict = match get_expected(part_report.part) {
+                None => Verdict::Missing { actual },
+                Some(expected) if expected == actual => Verdict::Pass,
+                Some(expected) => Verdict::Fail { expected, actual },
+            };
+
+            Check { day: report.day, part: part_report.part, verdict }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verify::{parse_answers, check_report, Verdict};
+    use crate::daily_challenge::{DayReport, PartReport, Part, Answer};
+    use std::time::Duration;
+
+    #[test]
+    fn it_parses_answers() {
+        let answers = parse_answers("[day1]\npart1 = 514579\npart2 = \"241861950\"\n\n[day11]\npart1 = 37\n").unwrap();
+
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[&1].get(Part::One), Some("514579".to_string()));
+        assert_eq!(answers[&1].get(Part::Two), Some("241861950".to_string()));
+        assert_eq!(answers[&11].get(Part::Two), None);
+
+        assert!(parse_answers("[first]\npart1 = 1\n").is_err());
+    }
+
+    #[test]
+    fn it_checks_reports() {
+        let answers = parse_answers("[day1]\npart1 = 514579\npart2 = 1\n").unwrap();
+        let part_report = |part, value| PartReport { part, answer: Answer::new(value), duration: Duration::default() };
+        let report = DayReport {
+            day: 1,
+            warnings: vec![],
+            parse_duration: Duration::default(),
+            parts: vec![part_report(Part::One, 514579), part_report(Part::Two, 241861950)],
+        };
+
+        let checks = check_report(&report, |part| answers[&1].get(part));
+        assert_eq!(checks[0].verdict, Verdict::Pass);
+        assert_eq!(checks[1].verdict, Verdict::Fail { expected: "1".to_string(), actual: "241861950".to_string() });
+
+        let checks = check_report(&report, |_| None);
+        assert_eq!(checks[0].verdict, Verdict::Missing { actual: "514579".to_string() });
+    }
+}